@@ -0,0 +1,257 @@
+use std::collections::VecDeque;
+use std::io::{ErrorKind, SeekFrom};
+
+use futures_core::Stream;
+use futures_util::stream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+
+use crate::media::{MediaObject, MediaObjectAssembler};
+use crate::packet::parse_data_packet;
+use crate::{
+    parse_asf, parse_data_object_header, parse_header_object, DataObject, Error, Result, ASF,
+    DATA_OBJECT_GUID,
+};
+
+// Header Object の GUID, サイズ, 子オブジェクトの数, 予約領域
+const HEADER_OBJECT_HEADER_SIZE: usize = 30;
+// Data Object の GUID, サイズ, File ID, パケット数, 予約領域
+const DATA_OBJECT_HEADER_SIZE: usize = 50;
+// 一度に読み込む Header Object のサイズの上限
+const MAX_HEADER_OBJECT_SIZE: u64 = 64 * 1024 * 1024;
+
+// AsyncRead + AsyncSeek からASFファイルを読み込む
+pub struct AsyncAsfReader<R> {
+    reader: R,
+    // Header Object に含まれるオブジェクト
+    asf: ASF<'static>,
+    // data_packets は読み込まない
+    data_object: DataObject<'static>,
+    // 最初のデータパケットの位置
+    data_start: u64,
+}
+
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncAsfReader<R> {
+    // Header Object と Data Object の先頭までを読み込む
+    pub async fn new(mut reader: R) -> Result<AsyncAsfReader<R>> {
+        reader.seek(SeekFrom::Start(0)).await?;
+
+        let mut header = vec![0; HEADER_OBJECT_HEADER_SIZE];
+        reader.read_exact(&mut header).await?;
+        let (_, header_object) = parse_header_object(&header)?;
+        // サイズは信用せず、ヘッダー自身より小さいものと大きすぎるものを拒否する
+        if header_object.object_size < HEADER_OBJECT_HEADER_SIZE as u64
+            || header_object.object_size > MAX_HEADER_OBJECT_SIZE
+        {
+            return Err(Error::Parse(nom::ErrorKind::LengthValue));
+        }
+        header.resize(header_object.object_size as usize, 0);
+        reader
+            .read_exact(&mut header[HEADER_OBJECT_HEADER_SIZE..])
+            .await?;
+        let (_, asf) = parse_asf(&header)?;
+
+        let mut data_header = [0; DATA_OBJECT_HEADER_SIZE];
+        reader.read_exact(&mut data_header).await?;
+        if data_header[..16] != DATA_OBJECT_GUID {
+            return Err(Error::Parse(nom::ErrorKind::Tag));
+        }
+        let (_, data_object) = parse_data_object_header(&data_header[16..])?;
+
+        Ok(AsyncAsfReader {
+            reader,
+            asf: asf.into_owned(),
+            data_object: data_object.into_owned(),
+            data_start: header.len() as u64 + DATA_OBJECT_HEADER_SIZE as u64,
+        })
+    }
+
+    // Header Object に含まれるオブジェクト
+    pub fn header(&self) -> &ASF<'static> {
+        &self.asf
+    }
+
+    // Data Object のヘッダー. data_packets は常に空
+    pub fn data_object(&self) -> &DataObject<'static> {
+        &self.data_object
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // データパケットを先頭から読み込み、メディアオブジェクトを順に返す.
+    // DRM で保護されている場合は最初の要素が Error::Protected になる
+    pub fn media_objects(&mut self) -> impl Stream<Item = Result<MediaObject>> + '_ {
+        let packet_size = match self.asf.file_props_object {
+            Some(ref file_props_object) => file_props_object.min_data_packet_size,
+            None => 0,
+        };
+        // Data Object のサイズが不明な場合はファイルの終端まで読み込む
+        let data_end = self
+            .data_object
+            .object_size
+            .checked_sub(DATA_OBJECT_HEADER_SIZE as u64)
+            .filter(|&size| size > 0)
+            .map(|size| self.data_start + size);
+        let state = MediaObjectsState {
+            position: None,
+            data_end,
+            packet: vec![0; packet_size as usize],
+            assembler: MediaObjectAssembler::for_streams(&self.asf.stream_props_objects),
+            queue: VecDeque::new(),
+            protected: self.asf.is_protected(),
+            done: packet_size == 0,
+            reader: self,
+        };
+
+        stream::unfold(state, |mut state| async move {
+            if state.protected {
+                state.protected = false;
+                state.done = true;
+                return Some((Err(Error::Protected), state));
+            }
+            loop {
+                if let Some(object) = state.queue.pop_front() {
+                    return Some((Ok(object), state));
+                }
+                if state.done {
+                    return None;
+                }
+                match state.read_packet().await {
+                    Ok(true) => {}
+                    Ok(false) => state.done = true,
+                    Err(e) => {
+                        state.done = true;
+                        return Some((Err(e), state));
+                    }
+                }
+            }
+        })
+    }
+}
+
+struct MediaObjectsState<'r, R> {
+    reader: &'r mut AsyncAsfReader<R>,
+    // 次に読み込むパケットの位置. None の場合はまだシークしていない
+    position: Option<u64>,
+    data_end: Option<u64>,
+    packet: Vec<u8>,
+    assembler: MediaObjectAssembler,
+    queue: VecDeque<MediaObject>,
+    protected: bool,
+    done: bool,
+}
+
+impl<'r, R: AsyncRead + AsyncSeek + Unpin> MediaObjectsState<'r, R> {
+    // パケットを1つ読み込む. データの終端に達した場合は false
+    async fn read_packet(&mut self) -> Result<bool> {
+        let position = match self.position {
+            Some(position) => position,
+            None => {
+                let start = self.reader.data_start;
+                self.reader.reader.seek(SeekFrom::Start(start)).await?;
+                start
+            }
+        };
+        let packet_size = self.packet.len() as u64;
+        if let Some(data_end) = self.data_end {
+            if position + packet_size > data_end {
+                return Ok(false);
+            }
+        }
+
+        // Data Object のサイズが分かっている場合、途中で途切れたファイルはエラーにする
+        match self.reader.reader.read_exact(&mut self.packet).await {
+            Ok(_) => {}
+            Err(ref e) if e.kind() == ErrorKind::UnexpectedEof && self.data_end.is_none() => {
+                return Ok(false)
+            }
+            Err(e) => return Err(e.into()),
+        }
+        self.position = Some(position + packet_size);
+
+        let (_, packet) = parse_data_packet(&self.packet, packet_size as u32)?;
+        for payload in &packet.payloads {
+            self.assembler.push(payload, &mut self.queue);
+        }
+        Ok(true)
+    }
+}
+
+#[test]
+fn async_asf_reader_test1() {
+    use futures_util::StreamExt;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let input = include_bytes!("../assets/kte.asf");
+        let (_, ans) = parse_asf(input).unwrap();
+        let ans_objects = ans
+            .media_objects()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        let file = tokio::fs::File::open("assets/kte.asf").await.unwrap();
+        let mut reader = AsyncAsfReader::new(file).await.unwrap();
+        assert_eq!(
+            reader.header().stream_props_objects,
+            ans.stream_props_objects
+        );
+        assert_eq!(reader.data_object().total_data_packets, 153);
+
+        let objects = reader
+            .media_objects()
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(objects, ans_objects);
+    });
+}
+
+#[test]
+fn async_asf_reader_test2() {
+    use futures_util::StreamExt;
+    use std::io::Cursor;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let input = include_bytes!("../assets/kte.asf");
+
+        // Header Object のサイズが壊れている
+        for &object_size in &[10u64, u64::MAX] {
+            let mut corrupted = input.to_vec();
+            corrupted[16..24].copy_from_slice(&object_size.to_le_bytes());
+            match AsyncAsfReader::new(Cursor::new(corrupted)).await {
+                Err(Error::Parse(nom::ErrorKind::LengthValue)) => {}
+                v => panic!("{:?}", v.err()),
+            }
+        }
+
+        // Header Object の途中で途切れている
+        match AsyncAsfReader::new(Cursor::new(input[..100].to_vec())).await {
+            Err(Error::Io(ref e)) if e.kind() == ErrorKind::UnexpectedEof => {}
+            v => panic!("{:?}", v.err()),
+        }
+
+        // Data Object のパケットの途中で途切れている
+        let (_, asf) = parse_asf(input).unwrap();
+        let data_packets = &asf.data_object.as_ref().unwrap().data_packets;
+        let packet_size = asf.file_props_object.as_ref().unwrap().min_data_packet_size as usize;
+        let data_start = data_packets.as_ptr() as usize - input.as_ptr() as usize;
+        let truncated = input[..data_start + packet_size * 10 + packet_size / 2].to_vec();
+        let mut reader = AsyncAsfReader::new(Cursor::new(truncated)).await.unwrap();
+        let results = reader.media_objects().collect::<Vec<_>>().await;
+        assert!(results[..results.len() - 1].iter().all(|v| v.is_ok()));
+        match results.last() {
+            Some(Err(Error::Io(ref e))) if e.kind() == ErrorKind::UnexpectedEof => {}
+            v => panic!("{:?}", v),
+        }
+    });
+}
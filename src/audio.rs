@@ -0,0 +1,199 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use nom::{le_u16, le_u32, IResult};
+
+// WAVEFORMATEXTENSIBLE の Format Tag
+pub const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct WaveFormatExtensible<'a> {
+    // Valid Bits Per Sample, Samples Per Block のいずれか
+    pub samples: u16,
+    // スピーカーの配置. SPEAKER_FRONT_LEFT(0x1), SPEAKER_FRONT_RIGHT(0x2), ...
+    pub channel_mask: u32,
+    // KSDATAFORMAT_SUBTYPE_* の GUID. 先頭の2bytesがFormat Tagになる
+    pub sub_format: Cow<'a, [u8]>,
+}
+
+// WAVEFORMATEX
+#[derive(Debug, PartialEq, Eq)]
+pub struct AudioFormat<'a> {
+    // コーデックのID. WMA(0x0161), WMA Pro(0x0162), ...
+    pub format_tag: u16,
+    // チャンネル数
+    pub channels: u16,
+    // サンプリングレート
+    pub samples_per_sec: u32,
+    // 1秒あたりの平均バイト数
+    pub avg_bytes_per_sec: u32,
+    // ブロックのサイズ
+    pub block_align: u16,
+    // 1サンプルあたりのビット数
+    pub bits_per_sample: u16,
+    // Codec Specific Dataのサイズ (cbSize)
+    pub codec_specific_data_size: u16,
+    pub codec_specific_data: Cow<'a, [u8]>,
+    // format_tag が WAVE_FORMAT_EXTENSIBLE の場合に codec_specific_data の先頭に含まれる
+    pub extensible: Option<WaveFormatExtensible<'a>>,
+}
+
+impl<'a> AudioFormat<'a> {
+    // WAVE_FORMAT_EXTENSIBLE の場合は sub_format から Format Tag を取り出す
+    pub fn effective_format_tag(&self) -> u16 {
+        match self.extensible {
+            Some(ref extensible) => {
+                u16::from(extensible.sub_format[0]) | u16::from(extensible.sub_format[1]) << 8
+            }
+            None => self.format_tag,
+        }
+    }
+
+    // コーデックの略称. 不明な場合は None
+    pub fn codec_name(&self) -> Option<&'static str> {
+        let name = match self.effective_format_tag() {
+            0x0001 => "PCM",
+            0x0002 => "ADPCM",
+            0x0003 => "IEEE Float",
+            0x0006 => "A-law",
+            0x0007 => "mu-law",
+            0x000A => "WMA Voice",
+            0x0050 => "MPEG Audio",
+            0x0055 => "MP3",
+            0x0160 => "WMA v1",
+            0x0161 => "WMA",
+            0x0162 => "WMA Pro",
+            0x0163 => "WMA Lossless",
+            0x2000 => "AC-3",
+            _ => return None,
+        };
+        Some(name)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> AudioFormat<'static> {
+        AudioFormat {
+            format_tag: self.format_tag,
+            channels: self.channels,
+            samples_per_sec: self.samples_per_sec,
+            avg_bytes_per_sec: self.avg_bytes_per_sec,
+            block_align: self.block_align,
+            bits_per_sample: self.bits_per_sample,
+            codec_specific_data_size: self.codec_specific_data_size,
+            codec_specific_data: Cow::Owned(self.codec_specific_data.into_owned()),
+            extensible: self.extensible.map(|v| WaveFormatExtensible {
+                samples: v.samples,
+                channel_mask: v.channel_mask,
+                sub_format: Cow::Owned(v.sub_format.into_owned()),
+            }),
+        }
+    }
+}
+
+// "WMA 44.1 kHz stereo" のように表示する
+impl<'a> fmt::Display for AudioFormat<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.codec_name() {
+            Some(name) => write!(f, "{}", name)?,
+            None => write!(f, "0x{:04X}", self.effective_format_tag())?,
+        }
+        write!(f, " {} kHz", f64::from(self.samples_per_sec) / 1000.0)?;
+        match self.channels {
+            1 => write!(f, " mono"),
+            2 => write!(f, " stereo"),
+            6 => write!(f, " 5.1"),
+            8 => write!(f, " 7.1"),
+            n => write!(f, " {} channels", n),
+        }
+    }
+}
+
+named!(
+    parse_wave_format_extensible<WaveFormatExtensible>,
+    do_parse!(
+        samples: le_u16
+            >> channel_mask: le_u32
+            >> sub_format: take!(16)
+            >> (WaveFormatExtensible {
+                samples,
+                channel_mask,
+                sub_format: Cow::Borrowed(sub_format),
+            })
+    )
+);
+
+// Audio Media Type の Type-Specific Data を解析する
+pub fn parse_audio_format(input: &[u8]) -> IResult<&[u8], AudioFormat<'_>> {
+    do_parse!(
+        input,
+        format_tag: le_u16
+            >> channels: le_u16
+            >> samples_per_sec: le_u32
+            >> avg_bytes_per_sec: le_u32
+            >> block_align: le_u16
+            >> bits_per_sample: le_u16
+            >> codec_specific_data_size: le_u16
+            >> codec_specific_data: take!(codec_specific_data_size)
+            >> (AudioFormat {
+                format_tag,
+                channels,
+                samples_per_sec,
+                avg_bytes_per_sec,
+                block_align,
+                bits_per_sample,
+                codec_specific_data_size,
+                codec_specific_data: Cow::Borrowed(codec_specific_data),
+                extensible: if format_tag == WAVE_FORMAT_EXTENSIBLE {
+                    parse_wave_format_extensible(codec_specific_data)
+                        .ok()
+                        .map(|(_, v)| v)
+                } else {
+                    None
+                },
+            })
+    )
+}
+
+#[test]
+fn parse_audio_format_test1() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let audio_format = asf.stream_props_objects[0].audio_format().unwrap();
+
+    assert_eq!(
+        audio_format,
+        AudioFormat {
+            format_tag: 0x0161,
+            channels: 2,
+            samples_per_sec: 44100,
+            avg_bytes_per_sec: 8005,
+            block_align: 1487,
+            bits_per_sample: 16,
+            codec_specific_data_size: 10,
+            codec_specific_data: Cow::Owned(vec![0, 136, 0, 0, 15, 0, 0, 0, 0, 0]),
+            extensible: None,
+        }
+    );
+    assert_eq!(audio_format.to_string(), "WMA 44.1 kHz stereo");
+    assert_eq!(asf.stream_props_objects[1].audio_format(), None);
+}
+
+#[test]
+fn parse_audio_format_extensible_test() {
+    let mut input = vec![
+        0xFE, 0xFF, 6, 0, 0x80, 0xBB, 0, 0, 0, 0x65, 0x04, 0, 24, 0, 24, 0, 22, 0,
+    ];
+    input.extend_from_slice(&[24, 0, 0x3F, 0, 0, 0]);
+    // KSDATAFORMAT_SUBTYPE_PCM
+    input.extend_from_slice(&[
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B,
+        0x71,
+    ]);
+    let (_, audio_format) = parse_audio_format(&input).unwrap();
+
+    let extensible = audio_format.extensible.as_ref().unwrap();
+    assert_eq!(extensible.samples, 24);
+    assert_eq!(extensible.channel_mask, 0x3F);
+    assert_eq!(audio_format.effective_format_tag(), 0x0001);
+    assert_eq!(audio_format.to_string(), "PCM 48 kHz 5.1");
+}
@@ -0,0 +1,147 @@
+use std::borrow::Cow;
+
+use nom::{le_u32, le_u64};
+
+use crate::{decode_ascii, ASF, CONTENT_BRANDING_OBJECT_GUID};
+
+// Banner Image Type
+pub const BANNER_IMAGE_TYPE_NONE: u32 = 0;
+pub const BANNER_IMAGE_TYPE_BITMAP: u32 = 1;
+pub const BANNER_IMAGE_TYPE_JPEG: u32 = 2;
+pub const BANNER_IMAGE_TYPE_GIF: u32 = 3;
+
+// カバーアートと同じように扱える画像
+#[derive(Debug, PartialEq, Eq)]
+pub struct Picture<'a> {
+    // "image/jpeg" など
+    pub mime_type: &'static str,
+    pub data: Cow<'a, [u8]>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ContentBrandingObject<'a> {
+    // 2211B3FA-BD23-11D2-B4B7-00A0C955FC6E
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // なし(0), Bitmap(1), JPEG(2), GIF(3)
+    pub banner_image_type: u32,
+    // Banner Image Data のサイズ
+    pub banner_image_data_size: u32,
+    pub banner_image_data: Cow<'a, [u8]>,
+    // Banner Image URL のサイズ
+    pub banner_image_url_length: u32,
+    // ASCII. バナーをクリックしたときに開く URL
+    pub banner_image_url: Cow<'a, [u8]>,
+    // Copyright URL のサイズ
+    pub copyright_url_length: u32,
+    // ASCII
+    pub copyright_url: Cow<'a, [u8]>,
+}
+
+impl<'a> ContentBrandingObject<'a> {
+    // バナー画像. 画像が無い場合や種類が不明な場合は None
+    pub fn banner(&self) -> Option<Picture<'_>> {
+        let mime_type = match self.banner_image_type {
+            BANNER_IMAGE_TYPE_BITMAP => "image/bmp",
+            BANNER_IMAGE_TYPE_JPEG => "image/jpeg",
+            BANNER_IMAGE_TYPE_GIF => "image/gif",
+            _ => return None,
+        };
+        if self.banner_image_data.is_empty() {
+            return None;
+        }
+        Some(Picture {
+            mime_type,
+            data: Cow::Borrowed(&self.banner_image_data),
+        })
+    }
+
+    pub fn banner_image_url(&self) -> String {
+        decode_ascii(&self.banner_image_url)
+    }
+
+    pub fn copyright_url(&self) -> String {
+        decode_ascii(&self.copyright_url)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> ContentBrandingObject<'static> {
+        ContentBrandingObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            banner_image_type: self.banner_image_type,
+            banner_image_data_size: self.banner_image_data_size,
+            banner_image_data: Cow::Owned(self.banner_image_data.into_owned()),
+            banner_image_url_length: self.banner_image_url_length,
+            banner_image_url: Cow::Owned(self.banner_image_url.into_owned()),
+            copyright_url_length: self.copyright_url_length,
+            copyright_url: Cow::Owned(self.copyright_url.into_owned()),
+        }
+    }
+}
+
+named!(
+    pub parse_content_branding_object<ContentBrandingObject>,
+    do_parse!(
+        object_size: le_u64
+            >> banner_image_type: le_u32
+            >> banner_image_data_size: le_u32
+            >> banner_image_data: take!(banner_image_data_size)
+            >> banner_image_url_length: le_u32
+            >> banner_image_url: take!(banner_image_url_length)
+            >> copyright_url_length: le_u32
+            >> copyright_url: take!(copyright_url_length)
+            >> (ContentBrandingObject {
+                object_id: Cow::Borrowed(&CONTENT_BRANDING_OBJECT_GUID[..]),
+                object_size,
+                banner_image_type,
+                banner_image_data_size,
+                banner_image_data: Cow::Borrowed(banner_image_data),
+                banner_image_url_length,
+                banner_image_url: Cow::Borrowed(banner_image_url),
+                copyright_url_length,
+                copyright_url: Cow::Borrowed(copyright_url),
+            })
+    )
+);
+
+impl<'a> ASF<'a> {
+    // Content Branding Object のバナー画像
+    pub fn banner(&self) -> Option<Picture<'_>> {
+        self.content_branding_object
+            .as_ref()
+            .and_then(|v| v.banner())
+    }
+}
+
+#[test]
+fn parse_content_branding_object_test() {
+    let mut input = Vec::new();
+    input.extend_from_slice(&74u64.to_le_bytes());
+    input.extend_from_slice(&[2, 0, 0, 0, 4, 0, 0, 0, 0xFF, 0xD8, 0xFF, 0xD9]);
+    input.extend_from_slice(&[19, 0, 0, 0]);
+    input.extend_from_slice(b"http://example.com\0");
+    input.extend_from_slice(&[11, 0, 0, 0]);
+    input.extend_from_slice(b"http://c.d\0");
+    let (remain, content_branding_object) = parse_content_branding_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(
+        content_branding_object.banner_image_url(),
+        "http://example.com"
+    );
+    assert_eq!(content_branding_object.copyright_url(), "http://c.d");
+
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    assert_eq!(asf.banner(), None);
+    asf.content_branding_object = Some(Box::new(content_branding_object.into_owned()));
+    assert_eq!(
+        asf.banner(),
+        Some(Picture {
+            mime_type: "image/jpeg",
+            data: Cow::Borrowed(&[0xFF, 0xD8, 0xFF, 0xD9][..]),
+        })
+    );
+}
@@ -0,0 +1,274 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use nom::{le_u16, le_u32, le_u64};
+
+use crate::stream::StreamKind;
+use crate::utf16;
+use crate::{ASF, CODEC_LIST_OBJECT_GUID};
+
+// Codec Entry の Type
+pub const CODEC_TYPE_VIDEO: u16 = 0x0001;
+pub const CODEC_TYPE_AUDIO: u16 = 0x0002;
+pub const CODEC_TYPE_UNKNOWN: u16 = 0xFFFF;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CodecEntry<'a> {
+    // Video(0x0001), Audio(0x0002), Unknown(0xFFFF)
+    pub codec_type: u16,
+    // Codec Name の文字数. 終端のヌル文字を含む
+    pub codec_name_length: u16,
+    // UTF-16LE
+    pub codec_name: Cow<'a, [u8]>,
+    // Codec Description の文字数. 終端のヌル文字を含む
+    pub codec_description_length: u16,
+    // UTF-16LE. ビットレートなど
+    pub codec_description: Cow<'a, [u8]>,
+    // Codec Information のバイト数
+    pub codec_information_length: u16,
+    // Audio の場合はフォーマットタグ(2bytes), Video の場合は FourCC(4bytes)
+    pub codec_information: Cow<'a, [u8]>,
+}
+
+impl<'a> CodecEntry<'a> {
+    // "Windows Media Audio 9.2" など
+    pub fn name(&self) -> String {
+        utf16::decode(&self.codec_name)
+    }
+
+    // " 64 kbps, 44 kHz, stereo (A/V) 1-pass CBR" など
+    pub fn description(&self) -> String {
+        utf16::decode(&self.codec_description)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> CodecEntry<'static> {
+        CodecEntry {
+            codec_type: self.codec_type,
+            codec_name_length: self.codec_name_length,
+            codec_name: Cow::Owned(self.codec_name.into_owned()),
+            codec_description_length: self.codec_description_length,
+            codec_description: Cow::Owned(self.codec_description.into_owned()),
+            codec_information_length: self.codec_information_length,
+            codec_information: Cow::Owned(self.codec_information.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CodecListObject<'a> {
+    // 86D15240-311D-11D0-A3A4-00A0C90348F6
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // 86D15241-311D-11D0-A3A4-00A0C90348F6
+    pub reserved: Cow<'a, [u8]>,
+    // Codec Entries の総数
+    pub codec_entries_count: u32,
+    pub codec_entries: Vec<CodecEntry<'a>>,
+}
+
+impl<'a> CodecListObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> CodecListObject<'static> {
+        CodecListObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            reserved: Cow::Owned(self.reserved.into_owned()),
+            codec_entries_count: self.codec_entries_count,
+            codec_entries: self
+                .codec_entries
+                .into_iter()
+                .map(CodecEntry::into_owned)
+                .collect(),
+        }
+    }
+}
+
+named!(
+    parse_codec_entry<CodecEntry>,
+    do_parse!(
+        codec_type: le_u16
+            >> codec_name_length: le_u16
+            >> codec_name: take!(codec_name_length as usize * 2)
+            >> codec_description_length: le_u16
+            >> codec_description: take!(codec_description_length as usize * 2)
+            >> codec_information_length: le_u16
+            >> codec_information: take!(codec_information_length)
+            >> (CodecEntry {
+                codec_type,
+                codec_name_length,
+                codec_name: Cow::Borrowed(codec_name),
+                codec_description_length,
+                codec_description: Cow::Borrowed(codec_description),
+                codec_information_length,
+                codec_information: Cow::Borrowed(codec_information),
+            })
+    )
+);
+
+named!(
+    pub parse_codec_list_object<CodecListObject>,
+    do_parse!(
+        object_size: le_u64
+            >> reserved: take!(16)
+            >> codec_entries_count: le_u32
+            >> codec_entries:
+                many_m_n!(
+                    codec_entries_count as usize,
+                    codec_entries_count as usize,
+                    parse_codec_entry
+                )
+            >> (CodecListObject {
+                object_id: Cow::Borrowed(&CODEC_LIST_OBJECT_GUID[..]),
+                object_size,
+                reserved: Cow::Borrowed(reserved),
+                codec_entries_count,
+                codec_entries,
+            })
+    )
+);
+
+// ストリームごとのコーデック情報
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CodecInfo {
+    // ストリーム番号
+    pub stream_number: u8,
+    pub stream_kind: StreamKind,
+    // Codec List Object の Codec Name. 無い場合はフォーマットタグや FourCC から推定する
+    pub name: String,
+    // Codec List Object の Codec Description
+    pub description: String,
+    // Audio の場合のフォーマットタグ
+    pub format_tag: Option<u16>,
+    // Video の場合の FourCC
+    pub fourcc: Option<[u8; 4]>,
+}
+
+impl fmt::Display for CodecInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl<'a> ASF<'a> {
+    // Codec List Object と各ストリームのフォーマットタグ, FourCC からコーデックを特定する
+    pub fn codec_infos(&self) -> Vec<CodecInfo> {
+        let codec_entries: &[CodecEntry] = match self.codec_list_object {
+            Some(ref codec_list_object) => &codec_list_object.codec_entries,
+            None => &[],
+        };
+
+        let mut audio_index = 0;
+        let mut video_index = 0;
+        self.stream_props_objects
+            .iter()
+            .map(|stream_props_object| {
+                let stream_kind = stream_props_object.stream_kind();
+                let (codec_type, index, codec_information, fallback_name) = match (
+                    stream_props_object.audio_format(),
+                    stream_props_object.video_format(),
+                ) {
+                    (Some(audio_format), _) => {
+                        audio_index += 1;
+                        let codec_information = audio_format.format_tag.to_le_bytes().to_vec();
+                        let fallback_name = audio_format
+                            .codec_name()
+                            .map(|v| v.to_string())
+                            .unwrap_or_else(|| {
+                                format!("0x{:04X}", audio_format.effective_format_tag())
+                            });
+                        (
+                            CODEC_TYPE_AUDIO,
+                            audio_index - 1,
+                            codec_information,
+                            fallback_name,
+                        )
+                    }
+                    (_, Some(video_format)) => {
+                        video_index += 1;
+                        let codec_information = video_format.format_data.compression_id.to_vec();
+                        (
+                            CODEC_TYPE_VIDEO,
+                            video_index - 1,
+                            codec_information,
+                            video_format.fourcc(),
+                        )
+                    }
+                    _ => (CODEC_TYPE_UNKNOWN, 0, Vec::new(), String::new()),
+                };
+
+                // Codec Information が一致するものを優先し、無ければ同じ種類の中の順番で対応付ける
+                let codec_entry = codec_entries
+                    .iter()
+                    .find(|v| {
+                        v.codec_type == codec_type
+                            && !codec_information.is_empty()
+                            && *v.codec_information == codec_information[..]
+                    })
+                    .or_else(|| {
+                        codec_entries
+                            .iter()
+                            .filter(|v| {
+                                v.codec_type == codec_type && codec_type != CODEC_TYPE_UNKNOWN
+                            })
+                            .nth(index)
+                    });
+
+                CodecInfo {
+                    stream_number: stream_props_object.stream_number(),
+                    stream_kind,
+                    name: codec_entry.map(CodecEntry::name).unwrap_or(fallback_name),
+                    description: codec_entry.map(CodecEntry::description).unwrap_or_default(),
+                    format_tag: stream_props_object.audio_format().map(|v| v.format_tag),
+                    fourcc: stream_props_object
+                        .video_format()
+                        .map(|v| v.format_data.compression_id),
+                }
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn codec_infos_test1() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let codec_infos = asf.codec_infos();
+
+    assert_eq!(
+        codec_infos,
+        vec![
+            CodecInfo {
+                stream_number: 1,
+                stream_kind: StreamKind::Audio,
+                name: "Windows Media Audio 9.2".to_string(),
+                description: " 64 kbps, 44 kHz, stereo (A/V) 1-pass CBR".to_string(),
+                format_tag: Some(0x0161),
+                fourcc: None,
+            },
+            CodecInfo {
+                stream_number: 2,
+                stream_kind: StreamKind::Video,
+                name: "Windows Media Video 9".to_string(),
+                description: "".to_string(),
+                format_tag: None,
+                fourcc: Some(*b"WMV3"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn codec_infos_without_codec_list_test() {
+    let input = include_bytes!("../assets/320x180_10fps.asf");
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    asf.codec_list_object = None;
+
+    let names = asf
+        .codec_infos()
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["WMV2", "WMA"]);
+}
@@ -0,0 +1,64 @@
+use std::borrow::Cow;
+
+use nom::{le_u64, le_u8};
+
+use crate::COMPATIBILITY_OBJECT_GUID;
+
+// Compatibility Object の Profile. 常に2
+pub const COMPATIBILITY_PROFILE: u8 = 2;
+// Compatibility Object の Mode. 常に1
+pub const COMPATIBILITY_MODE: u8 = 1;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CompatibilityObject<'a> {
+    // 26F18B5D-4584-47EC-9F5F-0E651F0452C9
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // 常に2
+    pub profile: u8,
+    // 常に1
+    pub mode: u8,
+}
+
+impl<'a> CompatibilityObject<'a> {
+    // Profile と Mode が仕様で定められた値かどうか
+    pub fn is_valid(&self) -> bool {
+        self.profile == COMPATIBILITY_PROFILE && self.mode == COMPATIBILITY_MODE
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> CompatibilityObject<'static> {
+        CompatibilityObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            profile: self.profile,
+            mode: self.mode,
+        }
+    }
+}
+
+named!(
+    pub parse_compatibility_object<CompatibilityObject>,
+    do_parse!(
+        object_size: le_u64
+            >> profile: le_u8
+            >> mode: le_u8
+            >> (CompatibilityObject {
+                object_id: Cow::Borrowed(&COMPATIBILITY_OBJECT_GUID[..]),
+                object_size,
+                profile,
+                mode,
+            })
+    )
+);
+
+#[test]
+fn parse_compatibility_object_test() {
+    let input = [26, 0, 0, 0, 0, 0, 0, 0, 2, 1];
+    let (remain, compatibility_object) = parse_compatibility_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(compatibility_object.object_size, 26);
+    assert!(compatibility_object.is_valid());
+}
@@ -0,0 +1,164 @@
+use std::borrow::Cow;
+
+use nom::{le_u32, le_u64};
+
+use crate::error::{Error, Result};
+use crate::{
+    ASF, ERROR_CORRECTION_OBJECT_GUID,
+    STREAM_PROPERTIES_OBJECT_ERROR_CORRECTION_TYPE_NO_ERROR_CORRECTION_GUID,
+};
+
+// パケットの Error Correction Data の Type
+pub const ERROR_CORRECTION_TYPE_UNCORRECTED: u8 = 0;
+pub const ERROR_CORRECTION_TYPE_XOR_DATA: u8 = 1;
+pub const ERROR_CORRECTION_TYPE_PARITY_DATA: u8 = 2;
+
+// Error Correction Length Type が 00 の場合の Error Correction Data のサイズ
+const ERROR_CORRECTION_DATA_LENGTH: u8 = 2;
+// Error Correction Flags の Opaque Data Present
+const OPAQUE_DATA_PRESENT: u8 = 0x10;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ErrorCorrectionObject<'a> {
+    // 75B22635-668E-11CF-A6D9-00AA0062CE6C
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // GUID. No Error Correction, Audio Spread
+    pub error_correction_type: Cow<'a, [u8]>,
+    // Error Correction Data のサイズ
+    pub error_correction_data_length: u32,
+    pub error_correction_data: Cow<'a, [u8]>,
+}
+
+impl<'a> ErrorCorrectionObject<'a> {
+    // パケットに誤り訂正データが含まれないことを示しているかどうか
+    pub fn is_uncorrected(&self) -> bool {
+        self.error_correction_type[..]
+            == STREAM_PROPERTIES_OBJECT_ERROR_CORRECTION_TYPE_NO_ERROR_CORRECTION_GUID[..]
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> ErrorCorrectionObject<'static> {
+        ErrorCorrectionObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            error_correction_type: Cow::Owned(self.error_correction_type.into_owned()),
+            error_correction_data_length: self.error_correction_data_length,
+            error_correction_data: Cow::Owned(self.error_correction_data.into_owned()),
+        }
+    }
+}
+
+named!(
+    pub parse_error_correction_object<ErrorCorrectionObject>,
+    do_parse!(
+        object_size: le_u64
+            >> error_correction_type: take!(16)
+            >> error_correction_data_length: le_u32
+            >> error_correction_data: take!(error_correction_data_length)
+            >> (ErrorCorrectionObject {
+                object_id: Cow::Borrowed(&ERROR_CORRECTION_OBJECT_GUID[..]),
+                object_size,
+                error_correction_type: Cow::Borrowed(error_correction_type),
+                error_correction_data_length,
+                error_correction_data: Cow::Borrowed(error_correction_data),
+            })
+    )
+);
+
+impl<'a> ASF<'a> {
+    // Compatibility Object と Error Correction Object が、各パケットの Error Correction Data と
+    // 矛盾していないことを検証する
+    pub fn validate_error_correction(&self) -> Result<()> {
+        if let Some(ref header_extension_object) = self.header_extension_object {
+            if let Some(ref compatibility_object) = header_extension_object.compatibility_object {
+                if !compatibility_object.is_valid() {
+                    return Err(Error::InvalidCompatibility(
+                        compatibility_object.profile,
+                        compatibility_object.mode,
+                    ));
+                }
+            }
+        }
+
+        // Error Correction Object が無い場合も誤り訂正データは含まれない
+        let uncorrected = self
+            .error_correction_object
+            .as_ref()
+            .is_none_or(|v| v.is_uncorrected());
+        let packets = match self.packets() {
+            Some(packets) => packets,
+            None => return Ok(()),
+        };
+        for (i, packet) in packets.enumerate() {
+            let packet = packet?;
+            let flags = match packet.error_correction_flags {
+                Some(flags) => flags,
+                None => continue,
+            };
+            if flags & 0x0F != ERROR_CORRECTION_DATA_LENGTH
+                || flags & OPAQUE_DATA_PRESENT != 0
+                || (uncorrected
+                    && packet.error_correction_type() != Some(ERROR_CORRECTION_TYPE_UNCORRECTED))
+            {
+                return Err(Error::InvalidErrorCorrection(i as u64));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn parse_error_correction_object_test() {
+    let mut input = vec![46, 0, 0, 0, 0, 0, 0, 0];
+    input.extend_from_slice(
+        &crate::STREAM_PROPERTIES_OBJECT_ERROR_CORRECTION_TYPE_NO_ERROR_CORRECTION_GUID,
+    );
+    input.extend_from_slice(&[2, 0, 0, 0, 1, 2]);
+    let (remain, error_correction_object) = parse_error_correction_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert!(error_correction_object.is_uncorrected());
+    assert_eq!(
+        error_correction_object.error_correction_data,
+        Cow::Borrowed(&[1u8, 2][..])
+    );
+}
+
+#[test]
+fn validate_error_correction_test() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    assert!(asf.validate_error_correction().is_ok());
+
+    let mut input = vec![24, 0, 0, 0, 0, 0, 0, 0];
+    input.extend_from_slice(
+        &crate::STREAM_PROPERTIES_OBJECT_ERROR_CORRECTION_TYPE_NO_ERROR_CORRECTION_GUID,
+    );
+    input.extend_from_slice(&[0, 0, 0, 0]);
+    let (_, error_correction_object) = parse_error_correction_object(&input).unwrap();
+    asf.error_correction_object = Some(Box::new(error_correction_object.into_owned()));
+    assert!(asf.validate_error_correction().is_ok());
+
+    // 2番目のパケットの Error Correction Data の Type を XOR Data にする
+    let packet_size = asf.file_props_object.as_ref().unwrap().min_data_packet_size as usize;
+    let data_object = asf.data_object.as_mut().unwrap();
+    data_object.data_packets.to_mut()[packet_size + 1] = ERROR_CORRECTION_TYPE_XOR_DATA;
+    match asf.validate_error_correction() {
+        Err(Error::InvalidErrorCorrection(1)) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+
+    asf.header_extension_object
+        .as_mut()
+        .unwrap()
+        .compatibility_object
+        .as_mut()
+        .unwrap()
+        .mode = 0;
+    match asf.validate_error_correction() {
+        Err(Error::InvalidCompatibility(2, 0)) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+}
@@ -0,0 +1,247 @@
+use std::borrow::Cow;
+
+use nom::{le_u32, le_u64};
+
+use crate::utf16;
+use crate::{
+    decode_ascii, ASF, CONTENT_ENCRYPTION_OBJECT_GUID, DIGITAL_SIGNATURE_OBJECT_GUID,
+    EXTENDED_CONTENT_ENCRYPTION_OBJECT_GUID,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ContentEncryptionObject<'a> {
+    // 2211B3FB-BD23-11D2-B4B7-00A0C955FC6E
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // Secret Data のサイズ
+    pub secret_data_length: u32,
+    pub secret_data: Cow<'a, [u8]>,
+    // Protection Type のサイズ
+    pub protection_type_length: u32,
+    // ASCII. 常に "DRM"
+    pub protection_type: Cow<'a, [u8]>,
+    // Key ID のサイズ
+    pub key_id_length: u32,
+    // ASCII
+    pub key_id: Cow<'a, [u8]>,
+    // License URL のサイズ
+    pub license_url_length: u32,
+    // ASCII
+    pub license_url: Cow<'a, [u8]>,
+}
+
+impl<'a> ContentEncryptionObject<'a> {
+    pub fn protection_type(&self) -> String {
+        decode_ascii(&self.protection_type)
+    }
+
+    pub fn key_id(&self) -> String {
+        decode_ascii(&self.key_id)
+    }
+
+    pub fn license_url(&self) -> String {
+        decode_ascii(&self.license_url)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> ContentEncryptionObject<'static> {
+        ContentEncryptionObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            secret_data_length: self.secret_data_length,
+            secret_data: Cow::Owned(self.secret_data.into_owned()),
+            protection_type_length: self.protection_type_length,
+            protection_type: Cow::Owned(self.protection_type.into_owned()),
+            key_id_length: self.key_id_length,
+            key_id: Cow::Owned(self.key_id.into_owned()),
+            license_url_length: self.license_url_length,
+            license_url: Cow::Owned(self.license_url.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExtendedContentEncryptionObject<'a> {
+    // 298AE614-2622-4C17-B935-DAE07EE9289C
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // Data のサイズ
+    pub data_size: u32,
+    // WMRM, PlayReady のヘッダー. UTF-16LE の XML
+    pub data: Cow<'a, [u8]>,
+}
+
+impl<'a> ExtendedContentEncryptionObject<'a> {
+    // Data を XML の文字列として返す
+    pub fn xml(&self) -> String {
+        utf16::decode(&self.data)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> ExtendedContentEncryptionObject<'static> {
+        ExtendedContentEncryptionObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            data_size: self.data_size,
+            data: Cow::Owned(self.data.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DigitalSignatureObject<'a> {
+    // 2211B3FC-BD23-11D2-B4B7-00A0C955FC6E
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // 署名の種類. 常に2
+    pub signature_type: u32,
+    // Signature Data のサイズ
+    pub signature_data_length: u32,
+    pub signature_data: Cow<'a, [u8]>,
+}
+
+impl<'a> DigitalSignatureObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> DigitalSignatureObject<'static> {
+        DigitalSignatureObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            signature_type: self.signature_type,
+            signature_data_length: self.signature_data_length,
+            signature_data: Cow::Owned(self.signature_data.into_owned()),
+        }
+    }
+}
+
+named!(
+    pub parse_content_encryption_object<ContentEncryptionObject>,
+    do_parse!(
+        object_size: le_u64
+            >> secret_data_length: le_u32
+            >> secret_data: take!(secret_data_length)
+            >> protection_type_length: le_u32
+            >> protection_type: take!(protection_type_length)
+            >> key_id_length: le_u32
+            >> key_id: take!(key_id_length)
+            >> license_url_length: le_u32
+            >> license_url: take!(license_url_length)
+            >> (ContentEncryptionObject {
+                object_id: Cow::Borrowed(&CONTENT_ENCRYPTION_OBJECT_GUID[..]),
+                object_size,
+                secret_data_length,
+                secret_data: Cow::Borrowed(secret_data),
+                protection_type_length,
+                protection_type: Cow::Borrowed(protection_type),
+                key_id_length,
+                key_id: Cow::Borrowed(key_id),
+                license_url_length,
+                license_url: Cow::Borrowed(license_url),
+            })
+    )
+);
+
+named!(
+    pub parse_extended_content_encryption_object<ExtendedContentEncryptionObject>,
+    do_parse!(
+        object_size: le_u64
+            >> data_size: le_u32
+            >> data: take!(data_size)
+            >> (ExtendedContentEncryptionObject {
+                object_id: Cow::Borrowed(&EXTENDED_CONTENT_ENCRYPTION_OBJECT_GUID[..]),
+                object_size,
+                data_size,
+                data: Cow::Borrowed(data),
+            })
+    )
+);
+
+named!(
+    pub parse_digital_signature_object<DigitalSignatureObject>,
+    do_parse!(
+        object_size: le_u64
+            >> signature_type: le_u32
+            >> signature_data_length: le_u32
+            >> signature_data: take!(signature_data_length)
+            >> (DigitalSignatureObject {
+                object_id: Cow::Borrowed(&DIGITAL_SIGNATURE_OBJECT_GUID[..]),
+                object_size,
+                signature_type,
+                signature_data_length,
+                signature_data: Cow::Borrowed(signature_data),
+            })
+    )
+);
+
+impl<'a> ASF<'a> {
+    // DRM で保護されているかどうか. 保護されている場合、media_objects は Error::Protected を返す
+    pub fn is_protected(&self) -> bool {
+        self.content_encryption_object.is_some()
+            || self.extended_content_encryption_object.is_some()
+            || self.stream_props_objects.iter().any(|v| v.is_encrypted())
+    }
+}
+
+#[test]
+fn parse_content_encryption_object_test() {
+    let mut input = Vec::new();
+    input.extend_from_slice(&66u64.to_le_bytes());
+    input.extend_from_slice(&[4, 0, 0, 0, 1, 2, 3, 4]);
+    input.extend_from_slice(&[4, 0, 0, 0]);
+    input.extend_from_slice(b"DRM\0");
+    input.extend_from_slice(&[5, 0, 0, 0]);
+    input.extend_from_slice(b"key1\0");
+    input.extend_from_slice(&[13, 0, 0, 0]);
+    input.extend_from_slice(b"http://a.b/c\0");
+    let (remain, content_encryption_object) = parse_content_encryption_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(
+        content_encryption_object.secret_data,
+        Cow::Borrowed(&[1, 2, 3, 4][..])
+    );
+    assert_eq!(content_encryption_object.protection_type(), "DRM");
+    assert_eq!(content_encryption_object.key_id(), "key1");
+    assert_eq!(content_encryption_object.license_url(), "http://a.b/c");
+
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    assert!(!asf.is_protected());
+    asf.content_encryption_object = Some(Box::new(content_encryption_object.into_owned()));
+    assert!(asf.is_protected());
+
+    // 暗号化されたメディアオブジェクトは返さない
+    let mut media_objects = asf.media_objects().unwrap();
+    match media_objects.next() {
+        Some(Err(crate::Error::Protected)) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+    assert!(media_objects.next().is_none());
+}
+
+#[test]
+fn parse_extended_content_encryption_object_test() {
+    let xml = utf16::encode("<WRMHEADER version=\"4.0.0.0\"></WRMHEADER>");
+    let mut input = Vec::new();
+    input.extend_from_slice(&(28 + xml.len() as u64).to_le_bytes());
+    input.extend_from_slice(&(xml.len() as u32).to_le_bytes());
+    input.extend_from_slice(&xml);
+    let (remain, extended_content_encryption_object) =
+        parse_extended_content_encryption_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(
+        extended_content_encryption_object.xml(),
+        "<WRMHEADER version=\"4.0.0.0\"></WRMHEADER>"
+    );
+
+    let input = [36, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 4, 0, 0, 0, 9, 8, 7, 6];
+    let (_, digital_signature_object) = parse_digital_signature_object(&input).unwrap();
+    assert_eq!(digital_signature_object.signature_type, 2);
+    assert_eq!(
+        digital_signature_object.signature_data,
+        Cow::Borrowed(&[9, 8, 7, 6][..])
+    );
+}
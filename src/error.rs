@@ -0,0 +1,87 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::result;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum Error {
+    // ファイルの読み込みに失敗した
+    Io(io::Error),
+    // 入力データが途中で途切れている
+    Incomplete(nom::Needed),
+    // 入力データの解析に失敗した
+    Parse(nom::ErrorKind),
+    // ストリーム番号が 1 から 127 の範囲外
+    InvalidStreamNumber(u8),
+    // 同じストリーム番号が複数のストリームで使われている
+    DuplicateStreamNumber(u8),
+    // Compatibility Object の Profile, Mode が 2, 1 ではない
+    InvalidCompatibility(u8, u8),
+    // パケットの Error Correction Data が Error Correction Object と矛盾している. 値はパケットの番号
+    InvalidErrorCorrection(u64),
+    // パケットサイズが小さすぎてペイロードを格納できない
+    InvalidPacketSize(u32),
+    // 切り出す時間の範囲が空
+    InvalidTimeRange(Duration, Duration),
+    // 連結するファイルのストリームの構成かパケットサイズが異なる. 値は連結するファイルの番号.
+    // 先頭のファイルに Data Object が無い場合は 0
+    IncompatibleStreams(usize),
+    // 時間がミリ秒単位の u32 で表せない
+    InvalidTimestamp(Duration),
+    // DRM で保護されていて、メディアオブジェクトを復号できない
+    Protected,
+}
+
+pub type Result<T> = result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => write!(f, "I/O error: {}", e),
+            Error::Incomplete(ref needed) => write!(f, "incomplete input: {:?}", needed),
+            Error::Parse(ref kind) => write!(f, "parse error: {}", kind.description()),
+            Error::InvalidStreamNumber(n) => write!(f, "invalid stream number: {}", n),
+            Error::DuplicateStreamNumber(n) => write!(f, "duplicate stream number: {}", n),
+            Error::InvalidCompatibility(profile, mode) => write!(
+                f,
+                "invalid compatibility: profile={}, mode={}",
+                profile, mode
+            ),
+            Error::InvalidErrorCorrection(n) => {
+                write!(f, "invalid error correction data in packet {}", n)
+            }
+            Error::InvalidPacketSize(n) => write!(f, "invalid packet size: {}", n),
+            Error::InvalidTimeRange(start, end) => {
+                write!(f, "invalid time range: {:?}..{:?}", start, end)
+            }
+            Error::IncompatibleStreams(n) => write!(f, "incompatible streams in file {}", n),
+            Error::InvalidTimestamp(time) => write!(f, "invalid timestamp: {:?}", time),
+            Error::Protected => write!(f, "content is protected by DRM"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl<I> From<nom::Err<I>> for Error {
+    fn from(e: nom::Err<I>) -> Error {
+        match e {
+            nom::Err::Incomplete(needed) => Error::Incomplete(needed),
+            nom::Err::Error(c) | nom::Err::Failure(c) => Error::Parse(c.into_error_kind()),
+        }
+    }
+}
@@ -0,0 +1,566 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+
+use nom::{le_u16, le_u64, IResult};
+
+use crate::{
+    ADVANCED_MUTUAL_EXCLUSION_OBJECT_GUID, ASF, BITRATE_MUTUAL_EXCLUSION_OBJECT_GUID,
+    GROUP_MUTUAL_EXCLUSION_OBJECT_GUID, MUTUAL_EXCLUSION_TYPE_BITRATE_GUID,
+    MUTUAL_EXCLUSION_TYPE_LANGUAGE_GUID,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExclusionType {
+    // 言語の異なるストリーム
+    Language,
+    // ビットレートの異なるストリーム
+    Bitrate,
+    Unknown,
+}
+
+impl ExclusionType {
+    pub fn from_guid(guid: &[u8]) -> ExclusionType {
+        if guid == MUTUAL_EXCLUSION_TYPE_LANGUAGE_GUID {
+            ExclusionType::Language
+        } else if guid == MUTUAL_EXCLUSION_TYPE_BITRATE_GUID {
+            ExclusionType::Bitrate
+        } else {
+            ExclusionType::Unknown
+        }
+    }
+}
+
+// Bitrate Mutual Exclusion Object, Advanced Mutual Exclusion Object
+#[derive(Debug, PartialEq, Eq)]
+pub struct MutualExclusionObject<'a> {
+    // D6E229DC-35DA-11D1-9034-00A0C90349BE, A08649CF-4775-4670-8A16-6E35357566CD
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // GUID. Language, Bitrate, Unknown
+    pub exclusion_type: Cow<'a, [u8]>,
+    // Stream Numbers の総数
+    pub stream_numbers_count: u16,
+    // いずれか1つだけを再生するストリーム
+    pub stream_numbers: Vec<u16>,
+}
+
+impl<'a> MutualExclusionObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> MutualExclusionObject<'static> {
+        MutualExclusionObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            exclusion_type: Cow::Owned(self.exclusion_type.into_owned()),
+            stream_numbers_count: self.stream_numbers_count,
+            stream_numbers: self.stream_numbers,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GroupMutualExclusionRecord {
+    // Stream Numbers の総数
+    pub stream_count: u16,
+    // まとめて再生するストリーム
+    pub stream_numbers: Vec<u16>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct GroupMutualExclusionObject<'a> {
+    // D1465A40-5A79-4338-B71B-E36B8FD6C249
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // GUID. Language, Bitrate, Unknown
+    pub exclusion_type: Cow<'a, [u8]>,
+    // Records の総数
+    pub record_count: u16,
+    // いずれか1つだけを再生するストリームの組
+    pub records: Vec<GroupMutualExclusionRecord>,
+}
+
+impl<'a> GroupMutualExclusionObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> GroupMutualExclusionObject<'static> {
+        GroupMutualExclusionObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            exclusion_type: Cow::Owned(self.exclusion_type.into_owned()),
+            record_count: self.record_count,
+            records: self.records,
+        }
+    }
+}
+
+fn parse_mutual_exclusion_object<'a>(
+    input: &'a [u8],
+    object_id: &'static [u8],
+) -> IResult<&'a [u8], MutualExclusionObject<'a>> {
+    do_parse!(
+        input,
+        object_size: le_u64
+            >> exclusion_type: take!(16)
+            >> stream_numbers_count: le_u16
+            >> stream_numbers:
+                many_m_n!(
+                    stream_numbers_count as usize,
+                    stream_numbers_count as usize,
+                    le_u16
+                )
+            >> (MutualExclusionObject {
+                object_id: Cow::Borrowed(object_id),
+                object_size,
+                exclusion_type: Cow::Borrowed(exclusion_type),
+                stream_numbers_count,
+                stream_numbers,
+            })
+    )
+}
+
+named!(
+    pub parse_bitrate_mutual_exclusion_object<MutualExclusionObject>,
+    call!(
+        parse_mutual_exclusion_object,
+        &BITRATE_MUTUAL_EXCLUSION_OBJECT_GUID
+    )
+);
+
+named!(
+    pub parse_advanced_mutual_exclusion_object<MutualExclusionObject>,
+    call!(
+        parse_mutual_exclusion_object,
+        &ADVANCED_MUTUAL_EXCLUSION_OBJECT_GUID
+    )
+);
+
+named!(
+    parse_group_mutual_exclusion_record<GroupMutualExclusionRecord>,
+    do_parse!(
+        stream_count: le_u16
+            >> stream_numbers: many_m_n!(stream_count as usize, stream_count as usize, le_u16)
+            >> (GroupMutualExclusionRecord {
+                stream_count,
+                stream_numbers,
+            })
+    )
+);
+
+named!(
+    pub parse_group_mutual_exclusion_object<GroupMutualExclusionObject>,
+    do_parse!(
+        object_size: le_u64
+            >> exclusion_type: take!(16)
+            >> record_count: le_u16
+            >> records:
+                many_m_n!(
+                    record_count as usize,
+                    record_count as usize,
+                    parse_group_mutual_exclusion_record
+                )
+            >> (GroupMutualExclusionObject {
+                object_id: Cow::Borrowed(&GROUP_MUTUAL_EXCLUSION_OBJECT_GUID[..]),
+                object_size,
+                exclusion_type: Cow::Borrowed(exclusion_type),
+                record_count,
+                records,
+            })
+    )
+);
+
+// 互いに排他的なストリームの選択肢
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamGroup {
+    pub exclusion_type: ExclusionType,
+    // 選択肢ごとのストリーム番号. いずれか1つだけを再生する
+    pub alternatives: Vec<Vec<u8>>,
+}
+
+// Stream Prioritization Object の優先度
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamPriority {
+    // ストリーム番号
+    pub stream_number: u8,
+    // 帯域が足りない場合でも削除してはいけないかどうか
+    pub mandatory: bool,
+}
+
+// Bandwidth Sharing Object の共有された帯域
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedBandwidth {
+    // ストリーム全体で使用する帯域 (bits/sec)
+    pub data_bitrate: u32,
+    // 帯域を共有するストリーム
+    pub stream_numbers: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StreamGroups {
+    // 全てのストリーム番号
+    pub streams: Vec<u8>,
+    pub groups: Vec<StreamGroup>,
+    // 優先度の高い順
+    pub priorities: Vec<StreamPriority>,
+    pub shared_bandwidths: Vec<SharedBandwidth>,
+}
+
+impl StreamGroups {
+    // stream_number と同時に再生できないストリーム
+    pub fn alternatives(&self, stream_number: u8) -> Vec<u8> {
+        let mut alternatives = Vec::new();
+        for group in &self.groups {
+            if !group
+                .alternatives
+                .iter()
+                .any(|v| v.contains(&stream_number))
+            {
+                continue;
+            }
+            for alternative in &group.alternatives {
+                if alternative.contains(&stream_number) {
+                    continue;
+                }
+                for &v in alternative {
+                    if !alternatives.contains(&v) {
+                        alternatives.push(v);
+                    }
+                }
+            }
+        }
+        alternatives.sort();
+        alternatives
+    }
+
+    // Stream Prioritization Object で Mandatory になっているかどうか
+    pub fn is_mandatory(&self, stream_number: u8) -> bool {
+        self.priorities
+            .iter()
+            .any(|v| v.stream_number == stream_number && v.mandatory)
+    }
+
+    // streams を同時に再生するのに必要な帯域. 帯域を共有するストリームは data_bitrate を上限とする
+    pub fn bitrate<F>(&self, streams: &[u8], bitrate: F) -> u32
+    where
+        F: Fn(u8) -> u32,
+    {
+        let mut counted: Vec<u8> = Vec::new();
+        let mut total = 0u32;
+        for shared_bandwidth in &self.shared_bandwidths {
+            let shared = shared_bandwidth
+                .stream_numbers
+                .iter()
+                .filter(|v| streams.contains(v) && !counted.contains(v))
+                .cloned()
+                .collect::<Vec<_>>();
+            let sum = shared.iter().map(|&v| bitrate(v)).sum::<u32>();
+            total = total.saturating_add(sum.min(shared_bandwidth.data_bitrate));
+            counted.extend(shared);
+        }
+        streams
+            .iter()
+            .filter(|v| !counted.contains(v))
+            .fold(total, |total, &v| total.saturating_add(bitrate(v)))
+    }
+
+    // bandwidth (bits/sec) に収まるように、各グループからビットレートが最も高い選択肢を選ぶ.
+    // 収まる選択肢が無い場合はビットレートが最も低いものを選ぶ. それでも収まらず優先度が
+    // 分かる場合は優先度の低いストリームから削除する. Mandatory のストリームは削除しない
+    pub fn select<F>(&self, bandwidth: u32, bitrate: F) -> Vec<u8>
+    where
+        F: Fn(u8) -> u32,
+    {
+        let mut selected = self
+            .streams
+            .iter()
+            .cloned()
+            .filter(|v| {
+                !self
+                    .groups
+                    .iter()
+                    .any(|group| group.alternatives.iter().any(|a| a.contains(v)))
+            })
+            .collect::<Vec<_>>();
+        let mut excluded: Vec<u8> = Vec::new();
+
+        for group in &self.groups {
+            let current = self.bitrate(&selected, &bitrate);
+            let candidates = group
+                .alternatives
+                .iter()
+                .filter(|a| !a.iter().any(|v| excluded.contains(v)))
+                .map(|a| {
+                    let mut streams = selected.clone();
+                    streams.extend(a.iter().filter(|v| !selected.contains(v)));
+                    let cost = self.bitrate(&streams, &bitrate).saturating_sub(current);
+                    (a, cost)
+                })
+                .collect::<Vec<_>>();
+            let remaining = bandwidth.saturating_sub(current);
+            let chosen = candidates
+                .iter()
+                .find(|(a, _)| a.iter().all(|v| selected.contains(v)))
+                .or_else(|| {
+                    candidates
+                        .iter()
+                        .find(|(a, _)| a.iter().any(|&v| self.is_mandatory(v)))
+                })
+                .or_else(|| {
+                    candidates
+                        .iter()
+                        .filter(|(_, cost)| *cost <= remaining)
+                        .max_by_key(|(_, cost)| *cost)
+                })
+                .or_else(|| candidates.iter().min_by_key(|(_, cost)| *cost));
+            let chosen = match chosen {
+                Some(&(chosen, _)) => chosen,
+                None => continue,
+            };
+
+            for &v in chosen {
+                if !selected.contains(&v) {
+                    selected.push(v);
+                }
+            }
+            for alternative in &group.alternatives {
+                for &v in alternative {
+                    if !selected.contains(&v) && !excluded.contains(&v) {
+                        excluded.push(v);
+                    }
+                }
+            }
+        }
+
+        // 優先度の低い順. Stream Prioritization Object に無いストリームが最も低い
+        let rank = |stream_number: u8| {
+            self.priorities
+                .iter()
+                .position(|v| v.stream_number == stream_number)
+                .unwrap_or(usize::MAX)
+        };
+        while !self.priorities.is_empty() && self.bitrate(&selected, &bitrate) > bandwidth {
+            let dropped = selected
+                .iter()
+                .cloned()
+                .filter(|&v| !self.is_mandatory(v))
+                .max_by_key(|&v| (rank(v), v));
+            match dropped {
+                Some(dropped) => selected.retain(|&v| v != dropped),
+                None => break,
+            }
+        }
+
+        selected.sort();
+        selected
+    }
+}
+
+// u16 のストリーム番号を u8 にする. 1 から 127 の範囲外のものは壊れているので無視する
+fn to_stream_number(stream_number: u16) -> Option<u8> {
+    match u8::try_from(stream_number) {
+        Ok(v) if (1..=127).contains(&v) => Some(v),
+        _ => {
+            debug!("ignore invalid stream number: {}", stream_number);
+            None
+        }
+    }
+}
+
+impl<'a> ASF<'a> {
+    // Mutual Exclusion Object, Stream Prioritization Object, Bandwidth Sharing Object から
+    // ストリームの組み合わせの制約を返す
+    pub fn stream_groups(&self) -> StreamGroups {
+        let mut groups = Vec::new();
+        let mutual_exclusion_objects = self.bitrate_mutual_exclusion_objects.iter().chain(
+            self.header_extension_object
+                .iter()
+                .flat_map(|v| v.advanced_mutual_exclusion_objects.iter()),
+        );
+        for v in mutual_exclusion_objects {
+            groups.push(StreamGroup {
+                exclusion_type: ExclusionType::from_guid(&v.exclusion_type),
+                alternatives: v
+                    .stream_numbers
+                    .iter()
+                    .filter_map(|&stream_number| to_stream_number(stream_number))
+                    .map(|stream_number| vec![stream_number])
+                    .collect(),
+            });
+        }
+        if let Some(ref header_extension_object) = self.header_extension_object {
+            for v in &header_extension_object.group_mutual_exclusion_objects {
+                groups.push(StreamGroup {
+                    exclusion_type: ExclusionType::from_guid(&v.exclusion_type),
+                    alternatives: v
+                        .records
+                        .iter()
+                        .map(|record| -> Vec<u8> {
+                            record
+                                .stream_numbers
+                                .iter()
+                                .filter_map(|&v| to_stream_number(v))
+                                .collect()
+                        })
+                        .filter(|v| !v.is_empty())
+                        .collect(),
+                });
+            }
+        }
+
+        let mut priorities = Vec::new();
+        let mut shared_bandwidths = Vec::new();
+        if let Some(ref header_extension_object) = self.header_extension_object {
+            if let Some(ref v) = header_extension_object.stream_prioritization_object {
+                priorities = v
+                    .priority_records
+                    .iter()
+                    .filter_map(|record| {
+                        Some(StreamPriority {
+                            stream_number: to_stream_number(record.stream_number)?,
+                            mandatory: record.is_mandatory(),
+                        })
+                    })
+                    .collect();
+            }
+            shared_bandwidths = header_extension_object
+                .bandwidth_sharing_objects
+                .iter()
+                .map(|v| SharedBandwidth {
+                    data_bitrate: v.data_bitrate,
+                    stream_numbers: v
+                        .stream_numbers
+                        .iter()
+                        .filter_map(|&v| to_stream_number(v))
+                        .collect(),
+                })
+                .collect();
+        }
+
+        StreamGroups {
+            streams: self
+                .stream_props_objects
+                .iter()
+                .map(|v| v.stream_number())
+                .collect(),
+            groups,
+            priorities,
+            shared_bandwidths,
+        }
+    }
+
+    // ストリームの平均ビットレート (bits/sec). Bitrate Record が無い場合は Audio Media Type から求める
+    pub fn average_bitrate(&self, stream_number: u8) -> u32 {
+        if let Some(bitrate_record) = self.bitrate_record(stream_number) {
+            return bitrate_record.average_bitrate;
+        }
+        self.stream_props_objects
+            .iter()
+            .find(|v| v.stream_number() == stream_number)
+            .and_then(|v| v.audio_format())
+            .map(|v| v.avg_bytes_per_sec.saturating_mul(8))
+            .unwrap_or(0)
+    }
+
+    // bandwidth (bits/sec) で再生するストリームを選ぶ
+    pub fn select_streams(&self, bandwidth: u32) -> Vec<u8> {
+        self.stream_groups()
+            .select(bandwidth, |v| self.average_bitrate(v))
+    }
+}
+
+#[test]
+fn parse_mutual_exclusion_object_test() {
+    let mut input = vec![46, 0, 0, 0, 0, 0, 0, 0];
+    input.extend_from_slice(&MUTUAL_EXCLUSION_TYPE_BITRATE_GUID);
+    input.extend_from_slice(&[3, 0, 2, 0, 3, 0, 4, 0]);
+    let (remain, bitrate_mutual_exclusion_object) =
+        parse_bitrate_mutual_exclusion_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(
+        bitrate_mutual_exclusion_object.stream_numbers,
+        vec![2, 3, 4]
+    );
+
+    let mut input = vec![52, 0, 0, 0, 0, 0, 0, 0];
+    input.extend_from_slice(&MUTUAL_EXCLUSION_TYPE_LANGUAGE_GUID);
+    input.extend_from_slice(&[2, 0, 2, 0, 1, 0, 2, 0, 2, 0, 3, 0, 4, 0]);
+    let (remain, group_mutual_exclusion_object) =
+        parse_group_mutual_exclusion_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(
+        group_mutual_exclusion_object.records[1],
+        GroupMutualExclusionRecord {
+            stream_count: 2,
+            stream_numbers: vec![3, 4],
+        }
+    );
+
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    let mut bitrate_mutual_exclusion_object = bitrate_mutual_exclusion_object.into_owned();
+    // u8 に収まらないストリーム番号は無視する
+    bitrate_mutual_exclusion_object.stream_numbers.push(0x0102);
+    asf.bitrate_mutual_exclusion_objects
+        .push(bitrate_mutual_exclusion_object);
+    let stream_groups = asf.stream_groups();
+
+    assert_eq!(stream_groups.streams, vec![1, 2]);
+    assert_eq!(
+        stream_groups.groups,
+        vec![StreamGroup {
+            exclusion_type: ExclusionType::Bitrate,
+            alternatives: vec![vec![2], vec![3], vec![4]],
+        }]
+    );
+    assert_eq!(stream_groups.alternatives(2), vec![3, 4]);
+    assert_eq!(stream_groups.alternatives(1), Vec::<u8>::new());
+}
+
+#[test]
+fn stream_groups_select_test() {
+    // 1: 音声 64kbps, 2-4: 映像 300/700/1500kbps
+    let stream_groups = StreamGroups {
+        streams: vec![1, 2, 3, 4],
+        groups: vec![StreamGroup {
+            exclusion_type: ExclusionType::Bitrate,
+            alternatives: vec![vec![2], vec![3], vec![4]],
+        }],
+        priorities: Vec::new(),
+        shared_bandwidths: Vec::new(),
+    };
+    let bitrate = |v: u8| [0, 64_000, 300_000, 700_000, 1_500_000][v as usize];
+
+    assert_eq!(stream_groups.select(2_000_000, bitrate), vec![1, 4]);
+    assert_eq!(stream_groups.select(1_000_000, bitrate), vec![1, 3]);
+    assert_eq!(stream_groups.select(100_000, bitrate), vec![1, 2]);
+}
+
+#[test]
+fn stream_groups_select_priority_test() {
+    // 1: 音声 64kbps, 2: 映像 500kbps, 3, 4: 150kbps の帯域を共有する 100kbps のストリーム
+    let stream_groups = StreamGroups {
+        streams: vec![1, 2, 3, 4],
+        groups: Vec::new(),
+        priorities: vec![
+            StreamPriority {
+                stream_number: 2,
+                mandatory: true,
+            },
+            StreamPriority {
+                stream_number: 1,
+                mandatory: false,
+            },
+        ],
+        shared_bandwidths: vec![SharedBandwidth {
+            data_bitrate: 150_000,
+            stream_numbers: vec![3, 4],
+        }],
+    };
+    let bitrate = |v: u8| [0, 64_000, 500_000, 100_000, 100_000][v as usize];
+
+    assert_eq!(stream_groups.bitrate(&[1, 2, 3, 4], bitrate), 714_000);
+    assert_eq!(stream_groups.select(800_000, bitrate), vec![1, 2, 3, 4]);
+    assert_eq!(stream_groups.select(700_000, bitrate), vec![1, 2, 3]);
+    assert_eq!(stream_groups.select(100_000, bitrate), vec![2]);
+}
@@ -0,0 +1,228 @@
+use std::borrow::Cow;
+
+use nom::{le_u16, le_u32, le_u64, IResult};
+
+use crate::compatibility::{parse_compatibility_object, CompatibilityObject};
+use crate::exclusion::{
+    parse_advanced_mutual_exclusion_object, parse_group_mutual_exclusion_object,
+    GroupMutualExclusionObject, MutualExclusionObject,
+};
+use crate::language::{parse_language_list_object, LanguageListObject};
+use crate::metadata::{parse_metadata_library_object, MetadataLibraryObject};
+use crate::priority::{
+    parse_bandwidth_sharing_object, parse_stream_prioritization_object, BandwidthSharingObject,
+    StreamPrioritizationObject,
+};
+use crate::raw::{parse_raw_object, RawObject};
+use crate::stream::{parse_extended_stream_props_object, ExtendedStreamPropertiesObject};
+use crate::{
+    parse_guid, ADVANCED_MUTUAL_EXCLUSION_OBJECT_GUID, BANDWIDTH_SHARING_OBJECT_GUID,
+    COMPATIBILITY_OBJECT_GUID, EXTENDED_STREAM_PROPERTIES_OBJECT_GUID,
+    GROUP_MUTUAL_EXCLUSION_OBJECT_GUID, HEADER_EXTENSION_OBJECT_GUID, LANGUAGE_LIST_OBJECT_GUID,
+    METADATA_LIBRARY_OBJECT_GUID, STREAM_PRIORITIZATION_OBJECT_GUID,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct HeaderExtensionObject<'a> {
+    // 5FBF03B5-A92E-11CF-8EE3-00C00C205365
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // ABD3D211-A9BA-11CF-8EE6-00C00C205365
+    pub reserved_field_1: Cow<'a, [u8]>,
+    // 常に6
+    pub reserved_field_2: u16,
+    // Header Extension Data のサイズ
+    pub header_extension_data_size: u32,
+    pub advanced_mutual_exclusion_objects: Vec<MutualExclusionObject<'a>>,
+    pub group_mutual_exclusion_objects: Vec<GroupMutualExclusionObject<'a>>,
+    pub stream_prioritization_object: Option<Box<StreamPrioritizationObject<'a>>>,
+    pub bandwidth_sharing_objects: Vec<BandwidthSharingObject<'a>>,
+    pub language_list_object: Option<Box<LanguageListObject<'a>>>,
+    pub extended_stream_props_objects: Vec<ExtendedStreamPropertiesObject<'a>>,
+    pub metadata_library_object: Option<Box<MetadataLibraryObject<'a>>>,
+    pub compatibility_object: Option<Box<CompatibilityObject<'a>>>,
+    // 既知でないオブジェクト. offset は Header Extension Object の先頭からの位置で、
+    // parse_asf で解析した場合はファイルの先頭からの位置になる
+    pub unknown_objects: Vec<RawObject<'a>>,
+    // Header Extension Data に含まれるオブジェクトの GUID. 書き込み時に元の順序を再現するために使う
+    pub object_order: Vec<Cow<'a, [u8]>>,
+}
+
+impl<'a> HeaderExtensionObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> HeaderExtensionObject<'static> {
+        HeaderExtensionObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            reserved_field_1: Cow::Owned(self.reserved_field_1.into_owned()),
+            reserved_field_2: self.reserved_field_2,
+            header_extension_data_size: self.header_extension_data_size,
+            advanced_mutual_exclusion_objects: self
+                .advanced_mutual_exclusion_objects
+                .into_iter()
+                .map(MutualExclusionObject::into_owned)
+                .collect(),
+            group_mutual_exclusion_objects: self
+                .group_mutual_exclusion_objects
+                .into_iter()
+                .map(GroupMutualExclusionObject::into_owned)
+                .collect(),
+            stream_prioritization_object: self
+                .stream_prioritization_object
+                .map(|v| Box::new(v.into_owned())),
+            bandwidth_sharing_objects: self
+                .bandwidth_sharing_objects
+                .into_iter()
+                .map(BandwidthSharingObject::into_owned)
+                .collect(),
+            language_list_object: self.language_list_object.map(|v| Box::new(v.into_owned())),
+            extended_stream_props_objects: self
+                .extended_stream_props_objects
+                .into_iter()
+                .map(ExtendedStreamPropertiesObject::into_owned)
+                .collect(),
+            metadata_library_object: self
+                .metadata_library_object
+                .map(|v| Box::new(v.into_owned())),
+            compatibility_object: self.compatibility_object.map(|v| Box::new(v.into_owned())),
+            unknown_objects: self
+                .unknown_objects
+                .into_iter()
+                .map(RawObject::into_owned)
+                .collect(),
+            object_order: self
+                .object_order
+                .into_iter()
+                .map(|v| Cow::Owned(v.into_owned()))
+                .collect(),
+        }
+    }
+}
+
+// Header Extension Object の先頭から Header Extension Data までのサイズ
+const HEADER_EXTENSION_DATA_OFFSET: u64 = 46;
+
+// GUID に続くオブジェクトを Object Size から切り出す
+named!(
+    parse_extension_object<&[u8]>,
+    do_parse!(
+        object_size: verify!(peek!(le_u64), |v: u64| v >= 24)
+            >> object: take!(object_size - 16)
+            >> (object)
+    )
+);
+
+named!(
+    parse_header_extension_fields<(u64, &[u8], u16, &[u8])>,
+    do_parse!(
+        object_size: le_u64
+            >> reserved_field_1: take!(16)
+            >> reserved_field_2: le_u16
+            >> header_extension_data_size: le_u32
+            >> header_extension_data: take!(header_extension_data_size)
+            >> ((
+                object_size,
+                reserved_field_1,
+                reserved_field_2,
+                header_extension_data
+            ))
+    )
+);
+
+// Header Extension Data に含まれるオブジェクトのうち、既知のものを解析する
+pub fn parse_header_extension_object(input: &[u8]) -> IResult<&[u8], HeaderExtensionObject<'_>> {
+    let (input, (object_size, reserved_field_1, reserved_field_2, mut data)) =
+        try_parse!(input, parse_header_extension_fields);
+
+    let mut header_extension_object = HeaderExtensionObject {
+        object_id: Cow::Borrowed(&HEADER_EXTENSION_OBJECT_GUID[..]),
+        object_size,
+        reserved_field_1: Cow::Borrowed(reserved_field_1),
+        reserved_field_2,
+        header_extension_data_size: data.len() as u32,
+        advanced_mutual_exclusion_objects: Vec::new(),
+        group_mutual_exclusion_objects: Vec::new(),
+        stream_prioritization_object: None,
+        bandwidth_sharing_objects: Vec::new(),
+        language_list_object: None,
+        extended_stream_props_objects: Vec::new(),
+        metadata_library_object: None,
+        compatibility_object: None,
+        unknown_objects: Vec::new(),
+        object_order: Vec::new(),
+    };
+    let header_extension_data = data;
+    while !data.is_empty() {
+        let object_start = data;
+        let (remain, guid_arr) = try_parse!(data, parse_guid);
+        let (remain, object) = try_parse!(remain, parse_extension_object);
+        data = remain;
+        header_extension_object
+            .object_order
+            .push(Cow::Borrowed(guid_arr));
+        if guid_arr == ADVANCED_MUTUAL_EXCLUSION_OBJECT_GUID {
+            let (_, v) = try_parse!(object, parse_advanced_mutual_exclusion_object);
+            header_extension_object
+                .advanced_mutual_exclusion_objects
+                .push(v);
+        } else if guid_arr == GROUP_MUTUAL_EXCLUSION_OBJECT_GUID {
+            let (_, v) = try_parse!(object, parse_group_mutual_exclusion_object);
+            header_extension_object
+                .group_mutual_exclusion_objects
+                .push(v);
+        } else if guid_arr == STREAM_PRIORITIZATION_OBJECT_GUID {
+            let (_, v) = try_parse!(object, parse_stream_prioritization_object);
+            header_extension_object.stream_prioritization_object = Some(Box::new(v));
+        } else if guid_arr == BANDWIDTH_SHARING_OBJECT_GUID {
+            let (_, v) = try_parse!(object, parse_bandwidth_sharing_object);
+            header_extension_object.bandwidth_sharing_objects.push(v);
+        } else if guid_arr == LANGUAGE_LIST_OBJECT_GUID {
+            let (_, v) = try_parse!(object, parse_language_list_object);
+            header_extension_object.language_list_object = Some(Box::new(v));
+        } else if guid_arr == EXTENDED_STREAM_PROPERTIES_OBJECT_GUID {
+            let (_, v) = try_parse!(object, parse_extended_stream_props_object);
+            header_extension_object
+                .extended_stream_props_objects
+                .push(v);
+        } else if guid_arr == METADATA_LIBRARY_OBJECT_GUID {
+            let (_, v) = try_parse!(object, parse_metadata_library_object);
+            header_extension_object.metadata_library_object = Some(Box::new(v));
+        } else if guid_arr == COMPATIBILITY_OBJECT_GUID {
+            let (_, v) = try_parse!(object, parse_compatibility_object);
+            header_extension_object.compatibility_object = Some(Box::new(v));
+        } else {
+            debug!("unknown extension object: GUID={:?}", guid_arr);
+            let offset = HEADER_EXTENSION_DATA_OFFSET
+                + (header_extension_data.len() - object_start.len()) as u64;
+            let (_, v) = try_parse!(object_start, call!(parse_raw_object, offset));
+            header_extension_object.unknown_objects.push(v);
+        }
+    }
+    Ok((input, header_extension_object))
+}
+
+#[test]
+fn parse_header_extension_object_test() {
+    let mut input = vec![0; 8];
+    input.extend_from_slice(&[
+        17, 210, 211, 171, 186, 169, 207, 17, 142, 230, 0, 192, 12, 32, 83, 101,
+    ]);
+    input.extend_from_slice(&[6, 0, 46, 0, 0, 0]);
+    input.extend_from_slice(&ADVANCED_MUTUAL_EXCLUSION_OBJECT_GUID);
+    input.extend_from_slice(&[46, 0, 0, 0, 0, 0, 0, 0]);
+    input.extend_from_slice(&crate::MUTUAL_EXCLUSION_TYPE_LANGUAGE_GUID);
+    input.extend_from_slice(&[2, 0, 1, 0, 2, 0]);
+    let object_size = input.len() as u64 + 16;
+    input[..8].copy_from_slice(&object_size.to_le_bytes());
+    let (remain, header_extension_object) = parse_header_extension_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(
+        header_extension_object.advanced_mutual_exclusion_objects[0].stream_numbers,
+        vec![1, 2]
+    );
+    assert!(header_extension_object
+        .group_mutual_exclusion_objects
+        .is_empty());
+}
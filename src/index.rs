@@ -0,0 +1,126 @@
+use std::borrow::Cow;
+
+use crate::packet::Payload;
+use crate::{IndexEntry, SimpleIndexObject, SIMPLE_INDEX_OBJECT_GUID};
+
+// Simple Index Object のエントリー間の時間間隔. 1秒
+pub const INDEX_ENTRY_TIME_INTERVAL: u64 = 10_000_000;
+
+// キーフレームの表示時間(100ナノ秒単位)と、それを含むパケットの範囲
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyFrame {
+    pub presentation_time: u64,
+    pub first_packet: u32,
+    pub last_packet: u32,
+}
+
+// 1つの映像ストリームのキーフレームを集め、Simple Index Object を作る
+#[derive(Debug, Default)]
+pub struct SimpleIndexBuilder {
+    key_frames: Vec<KeyFrame>,
+    // 最後のキーフレームのメディアオブジェクトの番号. 分割されたペイロードの続きを判定する
+    media_object_number: Option<u32>,
+}
+
+impl SimpleIndexBuilder {
+    pub fn new() -> SimpleIndexBuilder {
+        SimpleIndexBuilder::default()
+    }
+
+    pub fn push(&mut self, key_frame: KeyFrame) {
+        self.key_frames.push(key_frame);
+    }
+
+    // packet_number 番目のパケットのペイロードを追加する. キーフレーム以外は無視する
+    pub fn push_payload(&mut self, payload: &Payload, packet_number: u32) {
+        if !payload.key_frame {
+            return;
+        }
+        if !payload.is_compressed() && payload.offset_into_media_object > 0 {
+            if self.media_object_number == Some(payload.media_object_number) {
+                if let Some(key_frame) = self.key_frames.last_mut() {
+                    key_frame.last_packet = packet_number;
+                }
+            }
+            return;
+        }
+        let presentation_time = match payload.presentation_time() {
+            Some(v) => u64::from(v) * 10_000,
+            None => return,
+        };
+        self.media_object_number = Some(payload.media_object_number);
+        self.key_frames.push(KeyFrame {
+            presentation_time,
+            first_packet: packet_number,
+            last_packet: packet_number,
+        });
+    }
+
+    // time(100ナノ秒単位)以前で最後のキーフレーム. 無い場合は最初のキーフレーム
+    fn index_entry(&self, time: u64) -> IndexEntry {
+        let key_frame = self
+            .key_frames
+            .iter()
+            .rev()
+            .find(|v| v.presentation_time <= time)
+            .or_else(|| self.key_frames.first());
+        match key_frame {
+            Some(v) => IndexEntry {
+                packet_number: v.first_packet,
+                packet_count: (v.last_packet - v.first_packet + 1).min(u32::from(u16::MAX)) as u16,
+            },
+            None => IndexEntry {
+                packet_number: 0,
+                packet_count: 0,
+            },
+        }
+    }
+
+    // play_duration(100ナノ秒単位, プリロールを含む)の終端までを interval ごとに索引する
+    pub fn build(
+        &self,
+        file_id: &[u8],
+        interval: u64,
+        play_duration: u64,
+    ) -> SimpleIndexObject<'static> {
+        let index_entries: Vec<IndexEntry> = (0..play_duration.div_ceil(interval) + 1)
+            .map(|i| self.index_entry(i * interval))
+            .collect();
+        SimpleIndexObject {
+            object_id: Cow::Borrowed(&SIMPLE_INDEX_OBJECT_GUID),
+            object_size: 56 + 6 * index_entries.len() as u64,
+            file_id: Cow::Owned(file_id.to_vec()),
+            index_entry_time_interval: interval,
+            maximum_packet_count: index_entries
+                .iter()
+                .map(|v| u32::from(v.packet_count))
+                .max()
+                .unwrap_or(0),
+            index_entries_count: index_entries.len() as u32,
+            index_entries,
+        }
+    }
+}
+
+#[test]
+fn simple_index_builder_test() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let file_props_object = asf.file_props_object.as_ref().unwrap();
+
+    // 映像ストリームのキーフレームから、元のファイルと同じ索引を作れる
+    let mut builder = SimpleIndexBuilder::new();
+    for (packet_number, packet) in asf.packets().unwrap().enumerate() {
+        for payload in &packet.unwrap().payloads {
+            if payload.stream_number == 2 {
+                builder.push_payload(payload, packet_number as u32);
+            }
+        }
+    }
+    let simple_index_object = builder.build(
+        &file_props_object.file_id,
+        INDEX_ENTRY_TIME_INTERVAL,
+        file_props_object.play_duration,
+    );
+    assert_eq!(simple_index_object, asf.simple_index_objects[0]);
+}
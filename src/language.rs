@@ -0,0 +1,233 @@
+use std::borrow::Cow;
+
+use nom::{le_u16, le_u64, le_u8};
+
+use crate::metadata::AttributeValue;
+use crate::stream::ExtendedStreamPropertiesObject;
+use crate::utf16;
+use crate::{ASF, LANGUAGE_LIST_OBJECT_GUID};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LanguageIdRecord<'a> {
+    // Language ID のバイト数
+    pub language_id_length: u8,
+    // UTF-16LE. RFC 1766 の言語タグ. "en-us", "ja", ...
+    pub language_id: Cow<'a, [u8]>,
+}
+
+impl<'a> LanguageIdRecord<'a> {
+    pub fn language_id(&self) -> String {
+        utf16::decode(&self.language_id)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> LanguageIdRecord<'static> {
+        LanguageIdRecord {
+            language_id_length: self.language_id_length,
+            language_id: Cow::Owned(self.language_id.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct LanguageListObject<'a> {
+    // 7C4346A9-EFE0-4BFC-B229-393EDE415C85
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // Language ID Records の総数
+    pub language_id_records_count: u16,
+    pub language_id_records: Vec<LanguageIdRecord<'a>>,
+}
+
+impl<'a> LanguageListObject<'a> {
+    // index 番目の言語タグ
+    pub fn language(&self, index: u16) -> Option<String> {
+        self.language_id_records
+            .get(index as usize)
+            .map(LanguageIdRecord::language_id)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> LanguageListObject<'static> {
+        LanguageListObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            language_id_records_count: self.language_id_records_count,
+            language_id_records: self
+                .language_id_records
+                .into_iter()
+                .map(LanguageIdRecord::into_owned)
+                .collect(),
+        }
+    }
+}
+
+named!(
+    parse_language_id_record<LanguageIdRecord>,
+    do_parse!(
+        language_id_length: le_u8
+            >> language_id: take!(language_id_length)
+            >> (LanguageIdRecord {
+                language_id_length,
+                language_id: Cow::Borrowed(language_id),
+            })
+    )
+);
+
+named!(
+    pub parse_language_list_object<LanguageListObject>,
+    do_parse!(
+        object_size: le_u64
+            >> language_id_records_count: le_u16
+            >> language_id_records:
+                many_m_n!(
+                    language_id_records_count as usize,
+                    language_id_records_count as usize,
+                    parse_language_id_record
+                )
+            >> (LanguageListObject {
+                object_id: Cow::Borrowed(&LANGUAGE_LIST_OBJECT_GUID[..]),
+                object_size,
+                language_id_records_count,
+                language_id_records,
+            })
+    )
+);
+
+// Language List Object の言語を解決した Metadata Library Object の属性
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LocalizedAttribute {
+    // ストリーム番号. ファイル全体の場合は 0
+    pub stream_number: u16,
+    // 言語タグ. Language List Object に無い場合は None
+    pub language: Option<String>,
+    pub name: String,
+    pub value: AttributeValue,
+}
+
+impl<'a> ASF<'a> {
+    // Language List Object の index 番目の言語タグ
+    pub fn language(&self, index: u16) -> Option<String> {
+        self.header_extension_object
+            .as_ref()?
+            .language_list_object
+            .as_ref()?
+            .language(index)
+    }
+
+    // ストリームの Extended Stream Properties Object
+    pub fn extended_stream_props_object(
+        &self,
+        stream_number: u8,
+    ) -> Option<&ExtendedStreamPropertiesObject<'a>> {
+        self.header_extension_object
+            .as_ref()?
+            .extended_stream_props_objects
+            .iter()
+            .find(|v| v.stream_number == u16::from(stream_number))
+    }
+
+    // ストリームの言語タグ
+    pub fn stream_language(&self, stream_number: u8) -> Option<String> {
+        let extended_stream_props_object = self.extended_stream_props_object(stream_number)?;
+        self.language(extended_stream_props_object.stream_language_id_index)
+    }
+
+    // ストリームの名前と、その言語タグの組
+    pub fn stream_names(&self, stream_number: u8) -> Vec<(Option<String>, String)> {
+        match self.extended_stream_props_object(stream_number) {
+            Some(extended_stream_props_object) => extended_stream_props_object
+                .stream_names
+                .iter()
+                .map(|v| (self.language(v.language_id_index), v.name()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Metadata Library Object の属性
+    pub fn localized_attributes(&self) -> Vec<LocalizedAttribute> {
+        let metadata_library_object = match self.header_extension_object {
+            Some(ref v) => match v.metadata_library_object {
+                Some(ref v) => v,
+                None => return Vec::new(),
+            },
+            None => return Vec::new(),
+        };
+        metadata_library_object
+            .description_records
+            .iter()
+            .map(|v| LocalizedAttribute {
+                stream_number: v.stream_number,
+                language: self.language(v.language_list_index),
+                name: v.name(),
+                value: v.value(),
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn stream_language_test() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+
+    assert_eq!(asf.language(0), Some("ja".to_string()));
+    assert_eq!(asf.language(1), None);
+    assert_eq!(asf.stream_language(1), Some("ja".to_string()));
+    assert_eq!(asf.stream_language(2), Some("ja".to_string()));
+    assert_eq!(asf.stream_language(3), None);
+    assert!(asf.stream_names(1).is_empty());
+    assert!(asf.localized_attributes().is_empty());
+}
+
+#[test]
+fn localized_attributes_test() {
+    let mut input = Vec::new();
+    input.extend_from_slice(&[0; 8]);
+    input.extend_from_slice(&[2, 0]);
+    for (language_list_index, stream_number, name, data_type, data) in &[
+        (0u16, 0u16, "Title\0", 0u16, utf16::encode("Hello\0")),
+        (1, 2, "WM/Rating\0", 3, vec![5, 0, 0, 0]),
+    ] {
+        let name = utf16::encode(name);
+        input.extend_from_slice(&language_list_index.to_le_bytes());
+        input.extend_from_slice(&stream_number.to_le_bytes());
+        input.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        input.extend_from_slice(&data_type.to_le_bytes());
+        input.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        input.extend_from_slice(&name);
+        input.extend_from_slice(data);
+    }
+    let object_size = input.len() as u64 + 16;
+    input[..8].copy_from_slice(&object_size.to_le_bytes());
+    let (remain, metadata_library_object) =
+        crate::metadata::parse_metadata_library_object(&input).unwrap();
+    assert!(remain.is_empty());
+
+    let input = include_bytes!("../assets/320x180_10fps.asf");
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    asf.header_extension_object
+        .as_mut()
+        .unwrap()
+        .metadata_library_object = Some(Box::new(metadata_library_object.into_owned()));
+
+    assert_eq!(
+        asf.localized_attributes(),
+        vec![
+            LocalizedAttribute {
+                stream_number: 0,
+                language: Some("en".to_string()),
+                name: "Title".to_string(),
+                value: AttributeValue::String("Hello".to_string()),
+            },
+            LocalizedAttribute {
+                stream_number: 2,
+                language: None,
+                name: "WM/Rating".to_string(),
+                value: AttributeValue::Dword(5),
+            },
+        ]
+    );
+}
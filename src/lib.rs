@@ -4,8 +4,74 @@ extern crate nom;
 #[macro_use]
 extern crate log;
 
+#[cfg(feature = "tokio")]
+pub mod async_reader;
+pub mod audio;
+pub mod branding;
+pub mod codec;
+pub mod compatibility;
+pub mod correction;
+pub mod encryption;
+mod error;
+pub mod exclusion;
+pub mod extension;
+mod index;
+pub mod language;
+pub mod marker;
+pub mod media;
+pub mod metadata;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+pub mod mux;
+pub mod packet;
+pub mod priority;
+pub mod raw;
+pub mod remux;
+pub mod script;
+pub mod spread;
+pub mod stream;
+mod utf16;
+pub mod video;
+pub mod writer;
+
+use std::borrow::Cow;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
 use nom::{le_u16, le_u32, le_u64, le_u8, IResult};
 
+#[cfg(feature = "tokio")]
+pub use async_reader::AsyncAsfReader;
+use audio::{parse_audio_format, AudioFormat};
+use branding::{parse_content_branding_object, ContentBrandingObject};
+#[cfg(test)]
+use codec::CodecEntry;
+use codec::{parse_codec_list_object, CodecListObject};
+#[cfg(test)]
+use compatibility::CompatibilityObject;
+use correction::{parse_error_correction_object, ErrorCorrectionObject};
+use encryption::{
+    parse_content_encryption_object, parse_digital_signature_object,
+    parse_extended_content_encryption_object, ContentEncryptionObject, DigitalSignatureObject,
+    ExtendedContentEncryptionObject,
+};
+pub use error::{Error, Result};
+use exclusion::{parse_bitrate_mutual_exclusion_object, MutualExclusionObject};
+use extension::{parse_header_extension_object, HeaderExtensionObject};
+#[cfg(test)]
+use language::{LanguageIdRecord, LanguageListObject};
+use marker::{parse_marker_object, MarkerObject};
+use media::{MediaObjectAssembler, MediaObjects};
+#[cfg(feature = "mmap")]
+pub use mmap::{open_mmap, MmapAsf};
+use packet::DataPackets;
+use raw::{parse_raw_object, RawObject};
+use script::{parse_script_command_object, ScriptCommandObject};
+use spread::{parse_audio_spread, AudioSpread};
+use stream::{parse_binary_format, parse_type_specific_data, StreamKind, TypeSpecificData};
+#[cfg(test)]
+use stream::{ExtendedStreamPropertiesObject, PayloadExtensionSystem};
+use video::{parse_video_format, VideoFormat};
+
 // http://uguisu.skr.jp/Windows/format_asf.html
 // https://tools.ietf.org/html/draft-fleischman-asf-01
 // http://drang.s4.xrea.com/program/tips/id3tag/wmp/
@@ -30,11 +96,136 @@ pub const STREAM_BITRATE_PROPERTIES_OBJECT_GUID: [u8; 16] = [
     0xCE, 0x75, 0xF8, 0x7B, 0x8D, 0x46, 0xD1, 0x11, 0x8D, 0x82, 0x00, 0x60, 0x97, 0xC9, 0xA2, 0xB2,
 ];
 
+// 86D15240-311D-11D0-A3A4-00A0C90348F6
+pub const CODEC_LIST_OBJECT_GUID: [u8; 16] = [
+    0x40, 0x52, 0xD1, 0x86, 0x1D, 0x31, 0xD0, 0x11, 0xA3, 0xA4, 0x00, 0xA0, 0xC9, 0x03, 0x48, 0xF6,
+];
+
+// F487CD01-A951-11CF-8EE6-00C00C205365
+pub const MARKER_OBJECT_GUID: [u8; 16] = [
+    0x01, 0xCD, 0x87, 0xF4, 0x51, 0xA9, 0xCF, 0x11, 0x8E, 0xE6, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65,
+];
+
+// 1EFB1A30-0B62-11D0-A39B-00A0C90348F6
+pub const SCRIPT_COMMAND_OBJECT_GUID: [u8; 16] = [
+    0x30, 0x1A, 0xFB, 0x1E, 0x62, 0x0B, 0xD0, 0x11, 0xA3, 0x9B, 0x00, 0xA0, 0xC9, 0x03, 0x48, 0xF6,
+];
+
+// 2211B3FB-BD23-11D2-B4B7-00A0C955FC6E
+pub const CONTENT_ENCRYPTION_OBJECT_GUID: [u8; 16] = [
+    0xFB, 0xB3, 0x11, 0x22, 0x23, 0xBD, 0xD2, 0x11, 0xB4, 0xB7, 0x00, 0xA0, 0xC9, 0x55, 0xFC, 0x6E,
+];
+
+// 298AE614-2622-4C17-B935-DAE07EE9289C
+pub const EXTENDED_CONTENT_ENCRYPTION_OBJECT_GUID: [u8; 16] = [
+    0x14, 0xE6, 0x8A, 0x29, 0x22, 0x26, 0x17, 0x4C, 0xB9, 0x35, 0xDA, 0xE0, 0x7E, 0xE9, 0x28, 0x9C,
+];
+
+// 2211B3FC-BD23-11D2-B4B7-00A0C955FC6E
+pub const DIGITAL_SIGNATURE_OBJECT_GUID: [u8; 16] = [
+    0xFC, 0xB3, 0x11, 0x22, 0x23, 0xBD, 0xD2, 0x11, 0xB4, 0xB7, 0x00, 0xA0, 0xC9, 0x55, 0xFC, 0x6E,
+];
+
+// 2211B3FA-BD23-11D2-B4B7-00A0C955FC6E
+pub const CONTENT_BRANDING_OBJECT_GUID: [u8; 16] = [
+    0xFA, 0xB3, 0x11, 0x22, 0x23, 0xBD, 0xD2, 0x11, 0xB4, 0xB7, 0x00, 0xA0, 0xC9, 0x55, 0xFC, 0x6E,
+];
+
+// 5FBF03B5-A92E-11CF-8EE3-00C00C205365
+pub const HEADER_EXTENSION_OBJECT_GUID: [u8; 16] = [
+    0xB5, 0x03, 0xBF, 0x5F, 0x2E, 0xA9, 0xCF, 0x11, 0x8E, 0xE3, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65,
+];
+
+// ABD3D211-A9BA-11CF-8EE6-00C00C205365. Header Extension Object の Reserved Field 1
+pub const HEADER_EXTENSION_OBJECT_RESERVED_GUID: [u8; 16] = [
+    0x11, 0xD2, 0xD3, 0xAB, 0xBA, 0xA9, 0xCF, 0x11, 0x8E, 0xE6, 0x00, 0xC0, 0x0C, 0x20, 0x53, 0x65,
+];
+
+// D6E229DC-35DA-11D1-9034-00A0C90349BE
+pub const BITRATE_MUTUAL_EXCLUSION_OBJECT_GUID: [u8; 16] = [
+    0xDC, 0x29, 0xE2, 0xD6, 0xDA, 0x35, 0xD1, 0x11, 0x90, 0x34, 0x00, 0xA0, 0xC9, 0x03, 0x49, 0xBE,
+];
+
+// A08649CF-4775-4670-8A16-6E35357566CD
+pub const ADVANCED_MUTUAL_EXCLUSION_OBJECT_GUID: [u8; 16] = [
+    0xCF, 0x49, 0x86, 0xA0, 0x75, 0x47, 0x70, 0x46, 0x8A, 0x16, 0x6E, 0x35, 0x35, 0x75, 0x66, 0xCD,
+];
+
+// D1465A40-5A79-4338-B71B-E36B8FD6C249
+pub const GROUP_MUTUAL_EXCLUSION_OBJECT_GUID: [u8; 16] = [
+    0x40, 0x5A, 0x46, 0xD1, 0x79, 0x5A, 0x38, 0x43, 0xB7, 0x1B, 0xE3, 0x6B, 0x8F, 0xD6, 0xC2, 0x49,
+];
+
+// D6E22A00-35DA-11D1-9034-00A0C90349BE
+pub const MUTUAL_EXCLUSION_TYPE_LANGUAGE_GUID: [u8; 16] = [
+    0x00, 0x2A, 0xE2, 0xD6, 0xDA, 0x35, 0xD1, 0x11, 0x90, 0x34, 0x00, 0xA0, 0xC9, 0x03, 0x49, 0xBE,
+];
+
+// D6E22A01-35DA-11D1-9034-00A0C90349BE
+pub const MUTUAL_EXCLUSION_TYPE_BITRATE_GUID: [u8; 16] = [
+    0x01, 0x2A, 0xE2, 0xD6, 0xDA, 0x35, 0xD1, 0x11, 0x90, 0x34, 0x00, 0xA0, 0xC9, 0x03, 0x49, 0xBE,
+];
+
+// D6E22A02-35DA-11D1-9034-00A0C90349BE
+pub const MUTUAL_EXCLUSION_TYPE_UNKNOWN_GUID: [u8; 16] = [
+    0x02, 0x2A, 0xE2, 0xD6, 0xDA, 0x35, 0xD1, 0x11, 0x90, 0x34, 0x00, 0xA0, 0xC9, 0x03, 0x49, 0xBE,
+];
+
+// D4FED15B-88D3-454F-81F0-ED5C45999E24
+pub const STREAM_PRIORITIZATION_OBJECT_GUID: [u8; 16] = [
+    0x5B, 0xD1, 0xFE, 0xD4, 0xD3, 0x88, 0x4F, 0x45, 0x81, 0xF0, 0xED, 0x5C, 0x45, 0x99, 0x9E, 0x24,
+];
+
+// A69609E6-517B-11D2-B6AF-00C04FD908E9
+pub const BANDWIDTH_SHARING_OBJECT_GUID: [u8; 16] = [
+    0xE6, 0x09, 0x96, 0xA6, 0x7B, 0x51, 0xD2, 0x11, 0xB6, 0xAF, 0x00, 0xC0, 0x4F, 0xD9, 0x08, 0xE9,
+];
+
+// AF6060AA-5197-11D2-B6AF-00C04FD908E9
+pub const BANDWIDTH_SHARING_TYPE_EXCLUSIVE_GUID: [u8; 16] = [
+    0xAA, 0x60, 0x60, 0xAF, 0x97, 0x51, 0xD2, 0x11, 0xB6, 0xAF, 0x00, 0xC0, 0x4F, 0xD9, 0x08, 0xE9,
+];
+
+// AF6060AB-5197-11D2-B6AF-00C04FD908E9
+pub const BANDWIDTH_SHARING_TYPE_PARTIAL_GUID: [u8; 16] = [
+    0xAB, 0x60, 0x60, 0xAF, 0x97, 0x51, 0xD2, 0x11, 0xB6, 0xAF, 0x00, 0xC0, 0x4F, 0xD9, 0x08, 0xE9,
+];
+
+// 7C4346A9-EFE0-4BFC-B229-393EDE415C85
+pub const LANGUAGE_LIST_OBJECT_GUID: [u8; 16] = [
+    0xA9, 0x46, 0x43, 0x7C, 0xE0, 0xEF, 0xFC, 0x4B, 0xB2, 0x29, 0x39, 0x3E, 0xDE, 0x41, 0x5C, 0x85,
+];
+
+// 14E6A5CB-C672-4332-8399-A96952065B5A
+pub const EXTENDED_STREAM_PROPERTIES_OBJECT_GUID: [u8; 16] = [
+    0xCB, 0xA5, 0xE6, 0x14, 0x72, 0xC6, 0x32, 0x43, 0x83, 0x99, 0xA9, 0x69, 0x52, 0x06, 0x5B, 0x5A,
+];
+
+// 44231C94-9498-49D1-A141-1D134E457054
+pub const METADATA_LIBRARY_OBJECT_GUID: [u8; 16] = [
+    0x94, 0x1C, 0x23, 0x44, 0x98, 0x94, 0xD1, 0x49, 0xA1, 0x41, 0x1D, 0x13, 0x4E, 0x45, 0x70, 0x54,
+];
+
+// 75B22635-668E-11CF-A6D9-00AA0062CE6C
+pub const ERROR_CORRECTION_OBJECT_GUID: [u8; 16] = [
+    0x35, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C,
+];
+
+// 26F18B5D-4584-47EC-9F5F-0E651F0452C9
+pub const COMPATIBILITY_OBJECT_GUID: [u8; 16] = [
+    0x5D, 0x8B, 0xF1, 0x26, 0x84, 0x45, 0xEC, 0x47, 0x9F, 0x5F, 0x0E, 0x65, 0x1F, 0x04, 0x52, 0xC9,
+];
+
 // 75B22636-668E-11CF-A6D9-00AA0062CE6C
 pub const DATA_OBJECT_GUID: [u8; 16] = [
     0x36, 0x26, 0xB2, 0x75, 0x8E, 0x66, 0xCF, 0x11, 0xA6, 0xD9, 0x00, 0xAA, 0x00, 0x62, 0xCE, 0x6C,
 ];
 
+// 33000890-E5B1-11CF-89F4-00A0C90349CB
+pub const SIMPLE_INDEX_OBJECT_GUID: [u8; 16] = [
+    0x90, 0x08, 0x00, 0x33, 0xB1, 0xE5, 0xCF, 0x11, 0x89, 0xF4, 0x00, 0xA0, 0xC9, 0x03, 0x49, 0xCB,
+];
+
 // F8699E40-5B4D-11CF-A8FD-00805F5C442B
 pub const STREAM_PROPERTIES_OBJECT_STREAM_TYPE_AUDIO_GUID: [u8; 16] = [
     0x40, 0x9E, 0x69, 0xF8, 0x4D, 0x5B, 0xCF, 0x11, 0xA8, 0xFD, 0x00, 0x80, 0x5F, 0x5C, 0x44, 0x2B,
@@ -45,106 +236,431 @@ pub const STREAM_PROPERTIES_OBJECT_STREAM_TYPE_VIDEO_GUID: [u8; 16] = [
     0xC0, 0xEF, 0x19, 0xBC, 0x4D, 0x5B, 0xCF, 0x11, 0xA8, 0xFD, 0x00, 0x80, 0x5F, 0x5C, 0x44, 0x2B,
 ];
 
+// 59DACFC0-59E6-11D0-A3AC-00A0C90348F6
+pub const STREAM_PROPERTIES_OBJECT_STREAM_TYPE_COMMAND_GUID: [u8; 16] = [
+    0xC0, 0xCF, 0xDA, 0x59, 0xE6, 0x59, 0xD0, 0x11, 0xA3, 0xAC, 0x00, 0xA0, 0xC9, 0x03, 0x48, 0xF6,
+];
+
+// B61BE100-5B4E-11CF-A8FD-00805F5C442B
+pub const STREAM_PROPERTIES_OBJECT_STREAM_TYPE_JFIF_GUID: [u8; 16] = [
+    0x00, 0xE1, 0x1B, 0xB6, 0x4E, 0x5B, 0xCF, 0x11, 0xA8, 0xFD, 0x00, 0x80, 0x5F, 0x5C, 0x44, 0x2B,
+];
+
+// 35907DE0-E415-11CF-A917-00805F5C442B
+pub const STREAM_PROPERTIES_OBJECT_STREAM_TYPE_DEGRADABLE_JPEG_GUID: [u8; 16] = [
+    0xE0, 0x7D, 0x90, 0x35, 0x15, 0xE4, 0xCF, 0x11, 0xA9, 0x17, 0x00, 0x80, 0x5F, 0x5C, 0x44, 0x2B,
+];
+
+// 91BD222C-F21C-497A-8B6D-5AA86BFC0185
+pub const STREAM_PROPERTIES_OBJECT_STREAM_TYPE_FILE_TRANSFER_GUID: [u8; 16] = [
+    0x2C, 0x22, 0xBD, 0x91, 0x1C, 0xF2, 0x7A, 0x49, 0x8B, 0x6D, 0x5A, 0xA8, 0x6B, 0xFC, 0x01, 0x85,
+];
+
+// 3AFB65E2-47EF-40F2-AC2C-70A90D71D343
+pub const STREAM_PROPERTIES_OBJECT_STREAM_TYPE_BINARY_GUID: [u8; 16] = [
+    0xE2, 0x65, 0xFB, 0x3A, 0xEF, 0x47, 0xF2, 0x40, 0xAC, 0x2C, 0x70, 0xA9, 0x0D, 0x71, 0xD3, 0x43,
+];
+
+// 20FB5700-5B55-11CF-A8FD-00805F5C442B
+pub const STREAM_PROPERTIES_OBJECT_ERROR_CORRECTION_TYPE_NO_ERROR_CORRECTION_GUID: [u8; 16] = [
+    0x00, 0x57, 0xFB, 0x20, 0x55, 0x5B, 0xCF, 0x11, 0xA8, 0xFD, 0x00, 0x80, 0x5F, 0x5C, 0x44, 0x2B,
+];
+
+// BFC3CD50-618F-11CF-8BB2-00AA00B4E220
+pub const STREAM_PROPERTIES_OBJECT_ERROR_CORRECTION_TYPE_AUDIO_SPREAD_GUID: [u8; 16] = [
+    0x50, 0xCD, 0xC3, 0xBF, 0x8F, 0x61, 0xCF, 0x11, 0x8B, 0xB2, 0x00, 0xAA, 0x00, 0xB4, 0xE2, 0x20,
+];
+
+// 776257D4-C627-41CB-8F81-7AC7FF1C40CC
+pub const WEB_STREAM_MEDIA_SUBTYPE_GUID: [u8; 16] = [
+    0xD4, 0x57, 0x62, 0x77, 0x27, 0xC6, 0xCB, 0x41, 0x8F, 0x81, 0x7A, 0xC7, 0xFF, 0x1C, 0x40, 0xCC,
+];
+
+// DA1E6B13-8359-4050-B398-388E965BF00C
+pub const WEB_STREAM_FORMAT_GUID: [u8; 16] = [
+    0x13, 0x6B, 0x1E, 0xDA, 0x59, 0x83, 0x50, 0x40, 0xB3, 0x98, 0x38, 0x8E, 0x96, 0x5B, 0xF0, 0x0C,
+];
+
 #[derive(Debug, PartialEq, Eq)]
-pub struct HeaderObject {
+pub struct HeaderObject<'a> {
     // 75B22630-668E-11CF-A6D9-00AA0062CE6C
-    object_id: Vec<u8>,
+    pub object_id: Cow<'a, [u8]>,
     // オブジェクト全体のサイズ
-    object_size: u64,
+    pub object_size: u64,
     // Header Object に含まれる、子オブジェクトの総数
-    num_header_objects: u32,
+    pub num_header_objects: u32,
     // 予約領域
-    reserved_1: u8,
+    pub reserved_1: u8,
     // 予約領域
-    reserved_2: u8,
+    pub reserved_2: u8,
+}
+
+impl<'a> HeaderObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> HeaderObject<'static> {
+        HeaderObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            num_header_objects: self.num_header_objects,
+            reserved_1: self.reserved_1,
+            reserved_2: self.reserved_2,
+        }
+    }
+}
+
+// File Properties Object の Broadcast Flag
+pub const FILE_PROPERTIES_FLAG_BROADCAST: u32 = 0x01;
+// File Properties Object の Seekable Flag
+pub const FILE_PROPERTIES_FLAG_SEEKABLE: u32 = 0x02;
+
+// 1601年1月1日から1970年1月1日までの100ナノ秒単位の時間
+const FILETIME_UNIX_EPOCH: u64 = 116_444_736_000_000_000;
+
+fn duration_from_100ns(value: u64) -> Duration {
+    Duration::new(value / 10_000_000, (value % 10_000_000) as u32 * 100)
+}
+
+// ヌル終端された ASCII 文字列を変換する
+fn decode_ascii(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .trim_end_matches('\0')
+        .to_string()
 }
 
 #[derive(Debug, PartialEq, Eq)]
-pub struct FilePropertiesObject {
+pub struct FilePropertiesObject<'a> {
     // 8CABDCA1-A947-11CF-8EE4-00C00C205365
-    object_id: Vec<u8>,
+    pub object_id: Cow<'a, [u8]>,
     // オブジェクト全体のサイズ
-    object_size: u64,
+    pub object_size: u64,
     // GUID
-    file_id: Vec<u8>,
+    pub file_id: Cow<'a, [u8]>,
     // このサイズはasfのファイルのサイズと等しくなります
-    file_size: u64,
+    pub file_size: u64,
     // ファイルの作成日 1601年1月1日からのナノ秒で表現
-    creation_date: u64,
+    pub creation_date: u64,
     // Windows Media Playerの統計情報に現れる受信したパケットに等しくなるはず。
-    data_packets_count: u64,
+    pub data_packets_count: u64,
     // 再生時間
-    play_duration: u64,
+    pub play_duration: u64,
     // 送信時間
-    send_duration: u64,
+    pub send_duration: u64,
     // ファイル再生を始める前に必要なバッファリング時間
-    preoll: u64,
+    pub preoll: u64,
     // フラグ. Broadcast Flag(1bit), Seekable Flag(1bit), Reserved(30bits)
-    flags: u32,
+    pub flags: u32,
     // 送信時における最小のデータパケットのサイズ
-    min_data_packet_size: u32,
+    pub min_data_packet_size: u32,
     // 送信時における最大のデータパケットのサイズ
-    max_data_packet_size: u32,
+    pub max_data_packet_size: u32,
     // 送信時における最大ビットレート
-    max_bitrate: u32,
+    pub max_bitrate: u32,
+}
+
+impl<'a> FilePropertiesObject<'a> {
+    // ブロードキャスト中のファイルかどうか. この場合サイズや再生時間は無効になる
+    pub fn is_broadcast(&self) -> bool {
+        self.flags & FILE_PROPERTIES_FLAG_BROADCAST != 0
+    }
+
+    // シーク可能かどうか
+    pub fn is_seekable(&self) -> bool {
+        self.flags & FILE_PROPERTIES_FLAG_SEEKABLE != 0
+    }
+
+    // ファイルの作成日
+    pub fn creation_time(&self) -> SystemTime {
+        if self.creation_date >= FILETIME_UNIX_EPOCH {
+            UNIX_EPOCH + duration_from_100ns(self.creation_date - FILETIME_UNIX_EPOCH)
+        } else {
+            UNIX_EPOCH - duration_from_100ns(FILETIME_UNIX_EPOCH - self.creation_date)
+        }
+    }
+
+    // ファイルのサイズ. ブロードキャストの場合は None
+    pub fn known_file_size(&self) -> Option<u64> {
+        if self.is_broadcast() {
+            return None;
+        }
+        Some(self.file_size)
+    }
+
+    // データパケットの数. ブロードキャストの場合は None
+    pub fn known_data_packets_count(&self) -> Option<u64> {
+        if self.is_broadcast() {
+            return None;
+        }
+        Some(self.data_packets_count)
+    }
+
+    // 再生時間. プリロールを含む. ブロードキャストの場合は None
+    pub fn play_time(&self) -> Option<Duration> {
+        if self.is_broadcast() {
+            return None;
+        }
+        Some(duration_from_100ns(self.play_duration))
+    }
+
+    // 送信時間. ブロードキャストの場合は None
+    pub fn send_time(&self) -> Option<Duration> {
+        if self.is_broadcast() {
+            return None;
+        }
+        Some(duration_from_100ns(self.send_duration))
+    }
+
+    // ファイル再生を始める前に必要なバッファリング時間
+    pub fn preroll_time(&self) -> Duration {
+        Duration::from_millis(self.preoll)
+    }
+
+    // プリロールを除いた実際の再生時間. ブロードキャストの場合は None
+    pub fn playback_duration(&self) -> Option<Duration> {
+        self.play_time().map(|play_time| {
+            play_time
+                .checked_sub(self.preroll_time())
+                .unwrap_or_default()
+        })
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> FilePropertiesObject<'static> {
+        FilePropertiesObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            file_id: Cow::Owned(self.file_id.into_owned()),
+            file_size: self.file_size,
+            creation_date: self.creation_date,
+            data_packets_count: self.data_packets_count,
+            play_duration: self.play_duration,
+            send_duration: self.send_duration,
+            preoll: self.preoll,
+            flags: self.flags,
+            min_data_packet_size: self.min_data_packet_size,
+            max_data_packet_size: self.max_data_packet_size,
+            max_bitrate: self.max_bitrate,
+        }
+    }
 }
 
+// Stream Properties Object の Stream Number
+pub const STREAM_PROPERTIES_FLAG_STREAM_NUMBER: u16 = 0x007F;
+// Stream Properties Object の Encrypted Content Flag
+pub const STREAM_PROPERTIES_FLAG_ENCRYPTED: u16 = 0x8000;
+
 #[derive(Debug, PartialEq, Eq)]
-pub struct StreamPropertiesObject {
+pub struct StreamPropertiesObject<'a> {
     // B7DC0791-A9B7-11CF-8EE6-00C00C205365
-    object_id: Vec<u8>,
+    pub object_id: Cow<'a, [u8]>,
     // オブジェクト全体のサイズ
-    object_size: u64,
+    pub object_size: u64,
     // Audio, VideoなどのGUIDが含まれる
-    stream_type: Vec<u8>,
+    pub stream_type: Cow<'a, [u8]>,
     // デジタルメディアストリームで利用される修正タイプGUID ASF_No_Error_Correction: 20FB5700-5B55-11CF-A8FD-00805F5C442B ASF_Audio_Spread:  	BFC3CD50-618F-11CF-8BB2-00AA00B4E220
-    error_correction_type: Vec<u8>,
+    pub error_correction_type: Cow<'a, [u8]>,
     // ストリームの表示時間オフセットを100名の秒単位で表現される
-    time_offset: u64,
+    pub time_offset: u64,
     // Type-Specific Dataのサイズ
-    type_specific_data_length: u32,
+    pub type_specific_data_length: u32,
     // Error Correction Dataのサイズ
-    error_correction_data_length: u32,
+    pub error_correction_data_length: u32,
     // フラグ. Stream Number(7bits), Reserved(8bits), Encrypted Content Flag(1bit)
-    flags: u16,
+    pub flags: u16,
     // 予約領域
-    reserved: u32,
-    type_specific_data: Vec<u8>,
-    error_correction_data: Vec<u8>,
+    pub reserved: u32,
+    pub type_specific_data: Cow<'a, [u8]>,
+    pub error_correction_data: Cow<'a, [u8]>,
+}
+
+impl<'a> StreamPropertiesObject<'a> {
+    // ストリーム番号. 有効な値は 1 から 127
+    pub fn stream_number(&self) -> u8 {
+        (self.flags & STREAM_PROPERTIES_FLAG_STREAM_NUMBER) as u8
+    }
+
+    // ストリームの内容が暗号化されているかどうか
+    pub fn is_encrypted(&self) -> bool {
+        self.flags & STREAM_PROPERTIES_FLAG_ENCRYPTED != 0
+    }
+
+    // ストリームの種類. Binary のうち Media Subtype が Web Stream のものは WebStream になる
+    pub fn stream_kind(&self) -> StreamKind {
+        match StreamKind::from_guid(&self.stream_type) {
+            StreamKind::Binary => match parse_binary_format(&self.type_specific_data) {
+                Ok((_, ref v)) if v.is_web_stream() => StreamKind::WebStream,
+                _ => StreamKind::Binary,
+            },
+            kind => kind,
+        }
+    }
+
+    // Error Correction Type が Audio Spread の場合、error_correction_data を解析する
+    pub fn audio_spread(&self) -> Option<AudioSpread<'_>> {
+        if *self.error_correction_type
+            != STREAM_PROPERTIES_OBJECT_ERROR_CORRECTION_TYPE_AUDIO_SPREAD_GUID
+        {
+            return None;
+        }
+        parse_audio_spread(&self.error_correction_data)
+            .ok()
+            .map(|(_, v)| v)
+    }
+
+    // ストリームの種類に従って type_specific_data を解析する
+    pub fn type_specific(&self) -> Option<TypeSpecificData<'_>> {
+        parse_type_specific_data(&self.type_specific_data, self.stream_kind())
+            .ok()
+            .and_then(|(_, v)| v)
+    }
+
+    // Audio ストリームの場合、type_specific_data を WAVEFORMATEX として解析する
+    pub fn audio_format(&self) -> Option<AudioFormat<'_>> {
+        if *self.stream_type != STREAM_PROPERTIES_OBJECT_STREAM_TYPE_AUDIO_GUID {
+            return None;
+        }
+        parse_audio_format(&self.type_specific_data)
+            .ok()
+            .map(|(_, v)| v)
+    }
+
+    // Video ストリームの場合、type_specific_data を BITMAPINFOHEADER を含む形式として解析する
+    pub fn video_format(&self) -> Option<VideoFormat<'_>> {
+        if *self.stream_type != STREAM_PROPERTIES_OBJECT_STREAM_TYPE_VIDEO_GUID {
+            return None;
+        }
+        parse_video_format(&self.type_specific_data)
+            .ok()
+            .map(|(_, v)| v)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> StreamPropertiesObject<'static> {
+        StreamPropertiesObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            stream_type: Cow::Owned(self.stream_type.into_owned()),
+            error_correction_type: Cow::Owned(self.error_correction_type.into_owned()),
+            time_offset: self.time_offset,
+            type_specific_data_length: self.type_specific_data_length,
+            error_correction_data_length: self.error_correction_data_length,
+            flags: self.flags,
+            reserved: self.reserved,
+            type_specific_data: Cow::Owned(self.type_specific_data.into_owned()),
+            error_correction_data: Cow::Owned(self.error_correction_data.into_owned()),
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct BitrateRecord {
     // Stream Number(7bits), Reserved(9bits)
-    flags: u16,
-    average_bitrate: u32,
+    pub flags: u16,
+    pub average_bitrate: u32,
+}
+
+impl BitrateRecord {
+    // ストリーム番号. 有効な値は 1 から 127
+    pub fn stream_number(&self) -> u8 {
+        (self.flags & STREAM_PROPERTIES_FLAG_STREAM_NUMBER) as u8
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
-pub struct StreamBitratePropertiesObject {
+pub struct StreamBitratePropertiesObject<'a> {
     // 7BF875CE-468D-11D1-8D82-006097C9A2B2
-    object_id: Vec<u8>,
+    pub object_id: Cow<'a, [u8]>,
     // オブジェクト全体のサイズ
-    object_size: u64,
+    pub object_size: u64,
     // Bitrate Records の総数
-    bitrate_records_count: u16,
-    bitrate_records: Vec<BitrateRecord>,
+    pub bitrate_records_count: u16,
+    pub bitrate_records: Vec<BitrateRecord>,
+}
+
+impl<'a> StreamBitratePropertiesObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> StreamBitratePropertiesObject<'static> {
+        StreamBitratePropertiesObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            bitrate_records_count: self.bitrate_records_count,
+            bitrate_records: self.bitrate_records,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
-pub struct DataObject {
+pub struct DataObject<'a> {
     // 75B22636-668E-11CF-A6D9-00AA0062CE6C
-    object_id: Vec<u8>,
+    pub object_id: Cow<'a, [u8]>,
     // オブジェクト全体のサイズ
-    object_size: u64,
+    pub object_size: u64,
     // GUID
-    file_id: Vec<u8>,
+    pub file_id: Cow<'a, [u8]>,
     // Data Objectに存在するエントリーの数
-    total_data_packets: u16,
+    pub total_data_packets: u64,
     // 予約領域
-    reserved: u16,
+    pub reserved: u16,
     // 実データ
-    data_packets: Vec<u8>,
+    pub data_packets: Cow<'a, [u8]>,
+}
+
+impl<'a> DataObject<'a> {
+    // packet_size はFile Properties Objectのデータパケットのサイズ
+    pub fn packets(&self, packet_size: u32) -> DataPackets<'_> {
+        DataPackets::new(&self.data_packets, packet_size)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> DataObject<'static> {
+        DataObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            file_id: Cow::Owned(self.file_id.into_owned()),
+            total_data_packets: self.total_data_packets,
+            reserved: self.reserved,
+            data_packets: Cow::Owned(self.data_packets.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct IndexEntry {
+    // キーフレームを含むパケットの番号
+    pub packet_number: u32,
+    // キーフレームが含まれるパケットの数
+    pub packet_count: u16,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SimpleIndexObject<'a> {
+    // 33000890-E5B1-11CF-89F4-00A0C90349CB
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // GUID
+    pub file_id: Cow<'a, [u8]>,
+    // エントリー間の時間間隔. 100ナノ秒単位
+    pub index_entry_time_interval: u64,
+    // エントリー中の最大の Packet Count
+    pub maximum_packet_count: u32,
+    // Index Entries の総数
+    pub index_entries_count: u32,
+    pub index_entries: Vec<IndexEntry>,
+}
+
+impl<'a> SimpleIndexObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> SimpleIndexObject<'static> {
+        SimpleIndexObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            file_id: Cow::Owned(self.file_id.into_owned()),
+            index_entry_time_interval: self.index_entry_time_interval,
+            maximum_packet_count: self.maximum_packet_count,
+            index_entries_count: self.index_entries_count,
+            index_entries: self.index_entries,
+        }
+    }
 }
 
 named!(
@@ -156,11 +672,11 @@ named!(
             >> reserved_1: le_u8
             >> reserved_2: le_u8
             >> (HeaderObject {
-                object_id: object_id.to_vec(),
-                object_size: object_size,
-                num_header_objects: num_header_objects,
-                reserved_1: reserved_1,
-                reserved_2: reserved_2,
+                object_id: Cow::Borrowed(object_id),
+                object_size,
+                num_header_objects,
+                reserved_1,
+                reserved_2,
             })
     )
 );
@@ -181,19 +697,19 @@ named!(
             >> max_data_packet_size: le_u32
             >> max_bitrate: le_u32
             >> (FilePropertiesObject {
-                object_id: FILE_PROPERTIES_OBJECT_GUID.to_vec(),
-                object_size: object_size,
-                file_id: file_id.to_vec(),
-                file_size: file_size,
-                creation_date: creation_date,
-                data_packets_count: data_packets_count,
-                play_duration: play_duration,
-                send_duration: send_duration,
-                preoll: preoll,
-                flags: flags,
-                min_data_packet_size: min_data_packet_size,
-                max_data_packet_size: max_data_packet_size,
-                max_bitrate: max_bitrate,
+                object_id: Cow::Borrowed(&FILE_PROPERTIES_OBJECT_GUID[..]),
+                object_size,
+                file_id: Cow::Borrowed(file_id),
+                file_size,
+                creation_date,
+                data_packets_count,
+                play_duration,
+                send_duration,
+                preoll,
+                flags,
+                min_data_packet_size,
+                max_data_packet_size,
+                max_bitrate,
             })
     )
 );
@@ -212,17 +728,17 @@ named!(
             >> type_specific_data: take!(type_specific_data_length)
             >> error_correction_data: take!(error_correction_data_length)
             >> (StreamPropertiesObject {
-                object_id: STREAM_PROPERTIES_OBJECT_GUID.to_vec(),
-                object_size: object_size,
-                stream_type: stream_type.to_vec(),
-                error_correction_type: error_correction_type.to_vec(),
-                time_offset: time_offset,
-                type_specific_data_length: type_specific_data_length,
-                error_correction_data_length: error_correction_data_length,
-                flags: flags,
-                reserved: reserved,
-                type_specific_data: type_specific_data.to_vec(),
-                error_correction_data: error_correction_data.to_vec(),
+                object_id: Cow::Borrowed(&STREAM_PROPERTIES_OBJECT_GUID[..]),
+                object_size,
+                stream_type: Cow::Borrowed(stream_type),
+                error_correction_type: Cow::Borrowed(error_correction_type),
+                time_offset,
+                type_specific_data_length,
+                error_correction_data_length,
+                flags,
+                reserved,
+                type_specific_data: Cow::Borrowed(type_specific_data),
+                error_correction_data: Cow::Borrowed(error_correction_data),
             })
     )
 );
@@ -233,8 +749,8 @@ named!(
         flags: le_u16
             >> average_bitrate: le_u32
             >> (BitrateRecord {
-                flags: flags,
-                average_bitrate: average_bitrate,
+                flags,
+                average_bitrate,
             })
     )
 );
@@ -251,96 +767,425 @@ named!(
                     parse_bitrate_record
                 )
             >> (StreamBitratePropertiesObject {
-                object_id: STREAM_BITRATE_PROPERTIES_OBJECT_GUID.to_vec(),
-                object_size: object_size,
-                bitrate_records_count: bitrate_records_count,
-                bitrate_records: bitrate_records,
+                object_id: Cow::Borrowed(&STREAM_BITRATE_PROPERTIES_OBJECT_GUID[..]),
+                object_size,
+                bitrate_records_count,
+                bitrate_records,
             })
     )
 );
 
+// data_packets は読み込まない
 named!(
-    parse_data_object_record<DataObject>,
+    parse_data_object_header<DataObject>,
     do_parse!(
         object_size: le_u64
             >> file_id: take!(16)
-            >> total_data_packets: le_u16
+            >> total_data_packets: le_u64
             >> reserved: le_u16
-            >> data_packets: take!(object_size - 44)
             >> (DataObject {
-                object_id: DATA_OBJECT_GUID.to_vec(),
-                object_size: object_size,
-                file_id: file_id.to_vec(),
-                total_data_packets: total_data_packets,
-                reserved: reserved,
-                data_packets: data_packets.to_vec(),
+                object_id: Cow::Borrowed(&DATA_OBJECT_GUID[..]),
+                object_size,
+                file_id: Cow::Borrowed(file_id),
+                total_data_packets,
+                reserved,
+                data_packets: Cow::Borrowed(&[]),
             })
     )
 );
 
+// Data Object のサイズが不明な場合(ブロードキャスト中のファイル)は入力の終端までをデータパケットとする
+fn parse_data_packets(input: &[u8], object_size: u64) -> IResult<&[u8], &[u8]> {
+    if object_size < 50 {
+        return Ok((&input[input.len()..], input));
+    }
+    take!(input, object_size - 50)
+}
+
 named!(
-    parse_guid<Vec<u8>>,
-    do_parse!(object_id: take!(16) >> (object_id.to_vec()))
+    parse_data_object_record<DataObject>,
+    do_parse!(
+        data_object: parse_data_object_header
+            >> data_packets: call!(parse_data_packets, data_object.object_size)
+            >> (DataObject {
+                data_packets: Cow::Borrowed(data_packets),
+                ..data_object
+            })
+    )
 );
 
 named!(
-    parse_object<Vec<u8>>,
-    do_parse!(object_size: le_u64 >> object: take!(object_size - 24) >> (object.to_vec()))
+    parse_index_entry<IndexEntry>,
+    do_parse!(
+        packet_number: le_u32
+            >> packet_count: le_u16
+            >> (IndexEntry {
+                packet_number,
+                packet_count,
+            })
+    )
+);
+
+named!(
+    parse_simple_index_object<SimpleIndexObject>,
+    do_parse!(
+        object_size: le_u64
+            >> file_id: take!(16)
+            >> index_entry_time_interval: le_u64
+            >> maximum_packet_count: le_u32
+            >> index_entries_count: le_u32
+            >> index_entries:
+                many_m_n!(
+                    index_entries_count as usize,
+                    index_entries_count as usize,
+                    parse_index_entry
+                )
+            >> (SimpleIndexObject {
+                object_id: Cow::Borrowed(&SIMPLE_INDEX_OBJECT_GUID[..]),
+                object_size,
+                file_id: Cow::Borrowed(file_id),
+                index_entry_time_interval,
+                maximum_packet_count,
+                index_entries_count,
+                index_entries,
+            })
+    )
 );
 
+named!(pub parse_guid<&[u8]>, take!(16));
+
 #[derive(Debug, PartialEq, Eq)]
-pub struct ASF {
-    header_object: HeaderObject,
-    file_props_object: Option<Box<FilePropertiesObject>>,
-    stream_props_objects: Vec<StreamPropertiesObject>,
-    stream_bitrate_props_object: Option<Box<StreamBitratePropertiesObject>>,
-    data_object: Option<Box<DataObject>>,
+pub struct ASF<'a> {
+    pub header_object: HeaderObject<'a>,
+    pub file_props_object: Option<Box<FilePropertiesObject<'a>>>,
+    pub stream_props_objects: Vec<StreamPropertiesObject<'a>>,
+    pub stream_bitrate_props_object: Option<Box<StreamBitratePropertiesObject<'a>>>,
+    pub codec_list_object: Option<Box<CodecListObject<'a>>>,
+    pub marker_object: Option<Box<MarkerObject<'a>>>,
+    pub script_command_object: Option<Box<ScriptCommandObject<'a>>>,
+    pub content_encryption_object: Option<Box<ContentEncryptionObject<'a>>>,
+    pub extended_content_encryption_object: Option<Box<ExtendedContentEncryptionObject<'a>>>,
+    pub digital_signature_object: Option<Box<DigitalSignatureObject<'a>>>,
+    pub content_branding_object: Option<Box<ContentBrandingObject<'a>>>,
+    pub bitrate_mutual_exclusion_objects: Vec<MutualExclusionObject<'a>>,
+    pub header_extension_object: Option<Box<HeaderExtensionObject<'a>>>,
+    pub error_correction_object: Option<Box<ErrorCorrectionObject<'a>>>,
+    // Header Object に含まれる既知でないオブジェクト
+    pub unknown_header_objects: Vec<RawObject<'a>>,
+    // Header Object に含まれるオブジェクトの GUID. 書き込み時に元の順序を再現するために使う
+    pub header_object_order: Vec<Cow<'a, [u8]>>,
+    pub data_object: Option<Box<DataObject<'a>>>,
+    pub simple_index_objects: Vec<SimpleIndexObject<'a>>,
+    // Header Object に続く既知でないオブジェクト
+    pub unknown_objects: Vec<RawObject<'a>>,
+    // Header Object に続くオブジェクトの GUID
+    pub object_order: Vec<Cow<'a, [u8]>>,
 }
 
-pub fn parse_asf(input: &[u8]) -> IResult<&[u8], ASF> {
+impl<'a> ASF<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> ASF<'static> {
+        ASF {
+            header_object: self.header_object.into_owned(),
+            file_props_object: self.file_props_object.map(|v| Box::new(v.into_owned())),
+            stream_props_objects: self
+                .stream_props_objects
+                .into_iter()
+                .map(StreamPropertiesObject::into_owned)
+                .collect(),
+            stream_bitrate_props_object: self
+                .stream_bitrate_props_object
+                .map(|v| Box::new(v.into_owned())),
+            codec_list_object: self.codec_list_object.map(|v| Box::new(v.into_owned())),
+            marker_object: self.marker_object.map(|v| Box::new(v.into_owned())),
+            script_command_object: self.script_command_object.map(|v| Box::new(v.into_owned())),
+            content_encryption_object: self
+                .content_encryption_object
+                .map(|v| Box::new(v.into_owned())),
+            extended_content_encryption_object: self
+                .extended_content_encryption_object
+                .map(|v| Box::new(v.into_owned())),
+            digital_signature_object: self
+                .digital_signature_object
+                .map(|v| Box::new(v.into_owned())),
+            content_branding_object: self
+                .content_branding_object
+                .map(|v| Box::new(v.into_owned())),
+            bitrate_mutual_exclusion_objects: self
+                .bitrate_mutual_exclusion_objects
+                .into_iter()
+                .map(MutualExclusionObject::into_owned)
+                .collect(),
+            header_extension_object: self
+                .header_extension_object
+                .map(|v| Box::new(v.into_owned())),
+            error_correction_object: self
+                .error_correction_object
+                .map(|v| Box::new(v.into_owned())),
+            unknown_header_objects: self
+                .unknown_header_objects
+                .into_iter()
+                .map(RawObject::into_owned)
+                .collect(),
+            header_object_order: self
+                .header_object_order
+                .into_iter()
+                .map(|v| Cow::Owned(v.into_owned()))
+                .collect(),
+            data_object: self.data_object.map(|v| Box::new(v.into_owned())),
+            simple_index_objects: self
+                .simple_index_objects
+                .into_iter()
+                .map(SimpleIndexObject::into_owned)
+                .collect(),
+            unknown_objects: self
+                .unknown_objects
+                .into_iter()
+                .map(RawObject::into_owned)
+                .collect(),
+            object_order: self
+                .object_order
+                .into_iter()
+                .map(|v| Cow::Owned(v.into_owned()))
+                .collect(),
+        }
+    }
+
+    // Data Object のパケットを先頭から順に解析する
+    pub fn packets(&self) -> Option<DataPackets<'_>> {
+        let packet_size = self.file_props_object.as_ref()?.min_data_packet_size;
+        self.data_object
+            .as_ref()
+            .map(|data_object| data_object.packets(packet_size))
+    }
+
+    // Data Object のパケットからメディアオブジェクトを順に組み立てる.
+    // DRM で保護されている場合は最初の要素が Error::Protected になる
+    pub fn media_objects(&self) -> Option<MediaObjects<'_>> {
+        let assembler = MediaObjectAssembler::for_streams(&self.stream_props_objects);
+        let protected = self.is_protected();
+        self.packets()
+            .map(|packets| MediaObjects::new(packets, assembler).protected(protected))
+    }
+
+    // 各ストリームと、そのストリームの Bitrate Record の組
+    pub fn streams_with_bitrates(
+        &self,
+    ) -> Vec<(&StreamPropertiesObject<'a>, Option<&BitrateRecord>)> {
+        self.stream_props_objects
+            .iter()
+            .map(|stream_props_object| {
+                let bitrate_record = self.bitrate_record(stream_props_object.stream_number());
+                (stream_props_object, bitrate_record)
+            })
+            .collect()
+    }
+
+    // stream_number の Bitrate Record
+    pub fn bitrate_record(&self, stream_number: u8) -> Option<&BitrateRecord> {
+        self.stream_bitrate_props_object
+            .as_ref()?
+            .bitrate_records
+            .iter()
+            .find(|bitrate_record| bitrate_record.stream_number() == stream_number)
+    }
+
+    // ストリーム番号が 1 から 127 の範囲にあり、重複していないことを検証する
+    pub fn validate_streams(&self) -> Result<()> {
+        validate_stream_numbers(
+            self.stream_props_objects
+                .iter()
+                .map(StreamPropertiesObject::stream_number),
+        )?;
+        if let Some(ref stream_bitrate_props_object) = self.stream_bitrate_props_object {
+            validate_stream_numbers(
+                stream_bitrate_props_object
+                    .bitrate_records
+                    .iter()
+                    .map(BitrateRecord::stream_number),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_stream_numbers<I: Iterator<Item = u8>>(stream_numbers: I) -> Result<()> {
+    let mut seen = [false; 128];
+    for stream_number in stream_numbers {
+        if stream_number == 0 {
+            return Err(Error::InvalidStreamNumber(stream_number));
+        }
+        if seen[stream_number as usize] {
+            return Err(Error::DuplicateStreamNumber(stream_number));
+        }
+        seen[stream_number as usize] = true;
+    }
+    Ok(())
+}
+
+pub fn parse_asf(input: &[u8]) -> IResult<&[u8], ASF<'_>> {
     let mut file_props_object: Option<Box<FilePropertiesObject>> = None;
     let mut stream_props_objects: Vec<StreamPropertiesObject> = Vec::new();
     let mut stream_bitrate_props_object: Option<Box<StreamBitratePropertiesObject>> = None;
+    let mut codec_list_object: Option<Box<CodecListObject>> = None;
+    let mut marker_object: Option<Box<MarkerObject>> = None;
+    let mut script_command_object: Option<Box<ScriptCommandObject>> = None;
+    let mut content_encryption_object: Option<Box<ContentEncryptionObject>> = None;
+    let mut extended_content_encryption_object: Option<Box<ExtendedContentEncryptionObject>> = None;
+    let mut digital_signature_object: Option<Box<DigitalSignatureObject>> = None;
+    let mut content_branding_object: Option<Box<ContentBrandingObject>> = None;
+    let mut bitrate_mutual_exclusion_objects: Vec<MutualExclusionObject> = Vec::new();
+    let mut header_extension_object: Option<Box<HeaderExtensionObject>> = None;
+    let mut error_correction_object: Option<Box<ErrorCorrectionObject>> = None;
     let mut data_object: Option<Box<DataObject>> = None;
+    let mut simple_index_objects: Vec<SimpleIndexObject> = Vec::new();
+    let mut unknown_header_objects: Vec<RawObject> = Vec::new();
+    let mut unknown_objects: Vec<RawObject> = Vec::new();
+    let mut header_object_order: Vec<Cow<[u8]>> = Vec::new();
+    let mut object_order: Vec<Cow<[u8]>> = Vec::new();
+
+    // 入力の先頭からの位置
+    let start = input;
+    let offset_of = |input: &[u8]| (start.len() - input.len()) as u64;
 
     let (mut input, header_object) = try_parse!(input, parse_header_object);
     for _ in 0..header_object.num_header_objects {
-        let (remain, guid) = try_parse!(input, parse_guid);
+        let object_start = input;
+        let (remain, guid_arr) = try_parse!(input, parse_guid);
+        header_object_order.push(Cow::Borrowed(guid_arr));
         input = remain;
-        let guid_arr = guid.as_slice();
-        if &guid_arr == &FILE_PROPERTIES_OBJECT_GUID {
+        if guid_arr == FILE_PROPERTIES_OBJECT_GUID {
             let (remain, file_props_object_r) = try_parse!(input, parse_file_props_object);
             file_props_object = Some(Box::new(file_props_object_r));
             input = remain;
-        } else if &guid_arr == &STREAM_PROPERTIES_OBJECT_GUID {
+        } else if guid_arr == STREAM_PROPERTIES_OBJECT_GUID {
             let (remain, stream_props_object_r) = try_parse!(input, parse_stream_props_object);
             stream_props_objects.push(stream_props_object_r);
             input = remain;
-        } else if &guid_arr == &STREAM_BITRATE_PROPERTIES_OBJECT_GUID {
+        } else if guid_arr == STREAM_BITRATE_PROPERTIES_OBJECT_GUID {
             let (remain, stream_bitrate_props_object_r) =
                 try_parse!(input, parse_stream_bitrate_props_object);
             stream_bitrate_props_object = Some(Box::new(stream_bitrate_props_object_r));
             input = remain;
-        } else if &guid_arr == &DATA_OBJECT_GUID {
-            let (remain, data_object_r) = try_parse!(input, parse_data_object_record);
-            data_object = Some(Box::new(data_object_r));
+        } else if guid_arr == CODEC_LIST_OBJECT_GUID {
+            let (remain, codec_list_object_r) = try_parse!(input, parse_codec_list_object);
+            codec_list_object = Some(Box::new(codec_list_object_r));
+            input = remain;
+        } else if guid_arr == MARKER_OBJECT_GUID {
+            let (remain, marker_object_r) = try_parse!(input, parse_marker_object);
+            marker_object = Some(Box::new(marker_object_r));
+            input = remain;
+        } else if guid_arr == SCRIPT_COMMAND_OBJECT_GUID {
+            let (remain, script_command_object_r) = try_parse!(input, parse_script_command_object);
+            script_command_object = Some(Box::new(script_command_object_r));
+            input = remain;
+        } else if guid_arr == CONTENT_ENCRYPTION_OBJECT_GUID {
+            let (remain, content_encryption_object_r) =
+                try_parse!(input, parse_content_encryption_object);
+            content_encryption_object = Some(Box::new(content_encryption_object_r));
+            input = remain;
+        } else if guid_arr == EXTENDED_CONTENT_ENCRYPTION_OBJECT_GUID {
+            let (remain, extended_content_encryption_object_r) =
+                try_parse!(input, parse_extended_content_encryption_object);
+            extended_content_encryption_object =
+                Some(Box::new(extended_content_encryption_object_r));
+            input = remain;
+        } else if guid_arr == DIGITAL_SIGNATURE_OBJECT_GUID {
+            let (remain, digital_signature_object_r) =
+                try_parse!(input, parse_digital_signature_object);
+            digital_signature_object = Some(Box::new(digital_signature_object_r));
+            input = remain;
+        } else if guid_arr == CONTENT_BRANDING_OBJECT_GUID {
+            let (remain, content_branding_object_r) =
+                try_parse!(input, parse_content_branding_object);
+            content_branding_object = Some(Box::new(content_branding_object_r));
+            input = remain;
+        } else if guid_arr == BITRATE_MUTUAL_EXCLUSION_OBJECT_GUID {
+            let (remain, bitrate_mutual_exclusion_object_r) =
+                try_parse!(input, parse_bitrate_mutual_exclusion_object);
+            bitrate_mutual_exclusion_objects.push(bitrate_mutual_exclusion_object_r);
+            input = remain;
+        } else if guid_arr == HEADER_EXTENSION_OBJECT_GUID {
+            let (remain, mut header_extension_object_r) =
+                try_parse!(input, parse_header_extension_object);
+            // 既知でないオブジェクトの位置をファイルの先頭からの位置にする
+            for v in header_extension_object_r.unknown_objects.iter_mut() {
+                v.offset += offset_of(object_start);
+            }
+            header_extension_object = Some(Box::new(header_extension_object_r));
+            input = remain;
+        } else if guid_arr == ERROR_CORRECTION_OBJECT_GUID {
+            let (remain, error_correction_object_r) =
+                try_parse!(input, parse_error_correction_object);
+            error_correction_object = Some(Box::new(error_correction_object_r));
             input = remain;
         } else {
-            // skip this object
-            debug!("skip this object: GUID={:?}", guid_arr);
-            let (remain, _) = try_parse!(input, parse_object);
+            debug!("unknown object: GUID={:?}", guid_arr);
+            let (remain, raw_object) = try_parse!(
+                object_start,
+                call!(parse_raw_object, offset_of(object_start))
+            );
+            unknown_header_objects.push(raw_object);
             input = remain;
         }
     }
 
+    // Header Object に続く Data Object とインデックス. 入力が途中で途切れている場合はそこで終了する
+    while let Ok((remain, guid_arr)) = parse_guid(input) {
+        if guid_arr == DATA_OBJECT_GUID {
+            match complete!(remain, parse_data_object_record) {
+                Ok((remain, data_object_r)) => {
+                    object_order.push(Cow::Borrowed(guid_arr));
+                    data_object = Some(Box::new(data_object_r));
+                    input = remain;
+                }
+                Err(_) => break,
+            }
+        } else if guid_arr == SIMPLE_INDEX_OBJECT_GUID {
+            match complete!(remain, parse_simple_index_object) {
+                Ok((remain, simple_index_object_r)) => {
+                    object_order.push(Cow::Borrowed(guid_arr));
+                    simple_index_objects.push(simple_index_object_r);
+                    input = remain;
+                }
+                Err(_) => break,
+            }
+        } else {
+            debug!("unknown object: GUID={:?}", guid_arr);
+            match complete!(input, call!(parse_raw_object, offset_of(input))) {
+                Ok((remain, raw_object)) => {
+                    object_order.push(Cow::Borrowed(guid_arr));
+                    unknown_objects.push(raw_object);
+                    input = remain;
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
     Ok((
         input,
         ASF {
-            header_object: header_object,
-            file_props_object: file_props_object,
-            stream_props_objects: stream_props_objects,
-            stream_bitrate_props_object: stream_bitrate_props_object,
-            data_object: data_object,
+            header_object,
+            file_props_object,
+            stream_props_objects,
+            stream_bitrate_props_object,
+            codec_list_object,
+            marker_object,
+            script_command_object,
+            content_encryption_object,
+            extended_content_encryption_object,
+            digital_signature_object,
+            content_branding_object,
+            bitrate_mutual_exclusion_objects,
+            header_extension_object,
+            error_correction_object,
+            unknown_header_objects,
+            header_object_order,
+            data_object,
+            simple_index_objects,
+            unknown_objects,
+            object_order,
         },
     ))
 }
@@ -353,7 +1198,7 @@ fn parse_asf_test1() {
     match asf_obj {
         Ok((_, v)) => {
             let ans_header_object = HeaderObject {
-                object_id: HEADER_OBJECT_GUID.to_vec(),
+                object_id: Cow::Borrowed(&HEADER_OBJECT_GUID[..]),
                 object_size: 1106,
                 num_header_objects: 6,
                 reserved_1: 1,
@@ -361,9 +1206,9 @@ fn parse_asf_test1() {
             };
 
             let ans_file_props_object = FilePropertiesObject {
-                object_id: FILE_PROPERTIES_OBJECT_GUID.to_vec(),
+                object_id: Cow::Borrowed(&FILE_PROPERTIES_OBJECT_GUID[..]),
                 object_size: 104,
-                file_id: vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+                file_id: Cow::Owned(vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
                 file_size: 33248,
                 creation_date: 116444736000000000,
                 data_packets_count: 10,
@@ -378,54 +1223,216 @@ fn parse_asf_test1() {
 
             let ans_stream_props_objects = vec![
                 StreamPropertiesObject {
-                    object_id: STREAM_PROPERTIES_OBJECT_GUID.to_vec(),
+                    object_id: Cow::Borrowed(&STREAM_PROPERTIES_OBJECT_GUID[..]),
                     object_size: 133,
-                    stream_type: vec![
+                    stream_type: Cow::Owned(vec![
                         192, 239, 25, 188, 77, 91, 207, 17, 168, 253, 0, 128, 95, 92, 68, 43,
-                    ],
-                    error_correction_type: vec![
+                    ]),
+                    error_correction_type: Cow::Owned(vec![
                         0, 87, 251, 32, 85, 91, 207, 17, 168, 253, 0, 128, 95, 92, 68, 43,
-                    ],
+                    ]),
                     time_offset: 0,
                     type_specific_data_length: 55,
                     error_correction_data_length: 0,
                     flags: 1,
                     reserved: 0,
-                    type_specific_data: vec![
+                    type_specific_data: Cow::Owned(vec![
                         64, 1, 0, 0, 180, 0, 0, 0, 2, 44, 0, 44, 0, 0, 0, 64, 1, 0, 0, 180, 0, 0,
                         0, 1, 0, 24, 0, 87, 77, 86, 50, 0, 163, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                         0, 0, 0, 0, 0, 0, 80, 195, 180, 128,
-                    ],
-                    error_correction_data: vec![],
+                    ]),
+                    error_correction_data: Cow::Owned(vec![]),
                 },
                 StreamPropertiesObject {
-                    object_id: STREAM_PROPERTIES_OBJECT_GUID.to_vec(),
+                    object_id: Cow::Borrowed(&STREAM_PROPERTIES_OBJECT_GUID[..]),
                     object_size: 114,
-                    stream_type: vec![
+                    stream_type: Cow::Owned(vec![
                         64, 158, 105, 248, 77, 91, 207, 17, 168, 253, 0, 128, 95, 92, 68, 43,
-                    ],
-                    error_correction_type: vec![
+                    ]),
+                    error_correction_type: Cow::Owned(vec![
                         80, 205, 195, 191, 143, 97, 207, 17, 139, 178, 0, 170, 0, 180, 226, 32,
-                    ],
+                    ]),
                     time_offset: 0,
                     type_specific_data_length: 28,
                     error_correction_data_length: 8,
                     flags: 2,
                     reserved: 0,
-                    type_specific_data: vec![
+                    type_specific_data: Cow::Owned(vec![
                         97, 1, 1, 0, 68, 172, 0, 0, 160, 15, 0, 0, 185, 0, 16, 0, 10, 0, 0, 0, 0,
                         0, 1, 0, 0, 0, 0, 0,
-                    ],
-                    error_correction_data: vec![1, 185, 0, 185, 0, 1, 0, 0],
+                    ]),
+                    error_correction_data: Cow::Owned(vec![1, 185, 0, 185, 0, 1, 0, 0]),
                 },
             ];
 
+            let ans_codec_list_object = CodecListObject {
+                object_id: Cow::Borrowed(&CODEC_LIST_OBJECT_GUID[..]),
+                object_size: 122,
+                reserved: Cow::Owned(vec![
+                    65, 82, 209, 134, 29, 49, 208, 17, 163, 164, 0, 160, 201, 3, 72, 246,
+                ]),
+                codec_entries_count: 2,
+                codec_entries: vec![
+                    CodecEntry {
+                        codec_type: 1,
+                        codec_name_length: 5,
+                        codec_name: Cow::Owned(utf16::encode("wmv2\0")),
+                        codec_description_length: 0,
+                        codec_description: Cow::Owned(utf16::encode("")),
+                        codec_information_length: 4,
+                        codec_information: Cow::Owned(vec![87, 77, 86, 50]),
+                    },
+                    CodecEntry {
+                        codec_type: 2,
+                        codec_name_length: 23,
+                        codec_name: Cow::Owned(utf16::encode("Windows Media Audio V8\0")),
+                        codec_description_length: 0,
+                        codec_description: Cow::Owned(utf16::encode("")),
+                        codec_information_length: 2,
+                        codec_information: Cow::Owned(vec![97, 1]),
+                    },
+                ],
+            };
+
+            let ans_data_object = DataObject {
+                object_id: Cow::Borrowed(&DATA_OBJECT_GUID[..]),
+                object_size: 32050,
+                file_id: Cow::Owned(vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+                total_data_packets: 10,
+                reserved: 257,
+                data_packets: Cow::Borrowed(&input[1156..33156]),
+            };
+
+            let ans_simple_index_object = SimpleIndexObject {
+                object_id: Cow::Borrowed(&SIMPLE_INDEX_OBJECT_GUID[..]),
+                object_size: 92,
+                file_id: Cow::Owned(vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]),
+                index_entry_time_interval: 10000000,
+                maximum_packet_count: 0,
+                index_entries_count: 6,
+                index_entries: (0..6)
+                    .map(|_| IndexEntry {
+                        packet_number: 0,
+                        packet_count: 0,
+                    })
+                    .collect(),
+            };
+
             let ans = ASF {
                 header_object: ans_header_object,
                 file_props_object: Some(Box::new(ans_file_props_object)),
                 stream_props_objects: ans_stream_props_objects,
                 stream_bitrate_props_object: None,
-                data_object: None,
+                codec_list_object: Some(Box::new(ans_codec_list_object)),
+                marker_object: None,
+                script_command_object: None,
+                content_encryption_object: None,
+                extended_content_encryption_object: None,
+                digital_signature_object: None,
+                content_branding_object: None,
+                bitrate_mutual_exclusion_objects: Vec::new(),
+                header_extension_object: Some(Box::new(HeaderExtensionObject {
+                    object_id: Cow::Borrowed(&HEADER_EXTENSION_OBJECT_GUID[..]),
+                    object_size: 365,
+                    reserved_field_1: Cow::Owned(vec![
+                        17, 210, 211, 171, 186, 169, 207, 17, 142, 230, 0, 192, 12, 32, 83, 101,
+                    ]),
+                    reserved_field_2: 6,
+                    header_extension_data_size: 319,
+                    advanced_mutual_exclusion_objects: Vec::new(),
+                    group_mutual_exclusion_objects: Vec::new(),
+                    stream_prioritization_object: None,
+                    bandwidth_sharing_objects: Vec::new(),
+                    language_list_object: Some(Box::new(LanguageListObject {
+                        object_id: Cow::Borrowed(&LANGUAGE_LIST_OBJECT_GUID[..]),
+                        object_size: 33,
+                        language_id_records_count: 1,
+                        language_id_records: vec![LanguageIdRecord {
+                            language_id_length: 6,
+                            language_id: Cow::Owned(utf16::encode("en\0")),
+                        }],
+                    })),
+                    extended_stream_props_objects: vec![
+                        ExtendedStreamPropertiesObject {
+                            object_id: Cow::Borrowed(&EXTENDED_STREAM_PROPERTIES_OBJECT_GUID[..]),
+                            object_size: 88,
+                            start_time: 0,
+                            end_time: 0,
+                            data_bitrate: 200000,
+                            buffer_size: 5000,
+                            initial_buffer_fullness: 0,
+                            alternate_data_bitrate: 200000,
+                            alternate_buffer_size: 5000,
+                            alternate_initial_buffer_fullness: 0,
+                            maximum_object_size: 0,
+                            flags: 2,
+                            stream_number: 1,
+                            stream_language_id_index: 0,
+                            average_time_per_frame: 0,
+                            stream_name_count: 0,
+                            payload_extension_system_count: 0,
+                            stream_names: Vec::new(),
+                            payload_extension_systems: Vec::new(),
+                            stream_props_object: None,
+                        },
+                        ExtendedStreamPropertiesObject {
+                            object_id: Cow::Borrowed(&EXTENDED_STREAM_PROPERTIES_OBJECT_GUID[..]),
+                            object_size: 88,
+                            start_time: 0,
+                            end_time: 0,
+                            data_bitrate: 32000,
+                            buffer_size: 5000,
+                            initial_buffer_fullness: 0,
+                            alternate_data_bitrate: 32000,
+                            alternate_buffer_size: 5000,
+                            alternate_initial_buffer_fullness: 0,
+                            maximum_object_size: 0,
+                            flags: 2,
+                            stream_number: 2,
+                            stream_language_id_index: 0,
+                            average_time_per_frame: 0,
+                            stream_name_count: 0,
+                            payload_extension_system_count: 0,
+                            stream_names: Vec::new(),
+                            payload_extension_systems: Vec::new(),
+                            stream_props_object: None,
+                        },
+                    ],
+                    metadata_library_object: None,
+                    compatibility_object: None,
+                    unknown_objects: vec![RawObject {
+                        guid: Cow::Borrowed(&input[389..405]),
+                        offset: 389,
+                        bytes: Cow::Borrowed(&input[389..499]),
+                    }],
+                    object_order: vec![
+                        Cow::Borrowed(&LANGUAGE_LIST_OBJECT_GUID[..]),
+                        Cow::Borrowed(&EXTENDED_STREAM_PROPERTIES_OBJECT_GUID[..]),
+                        Cow::Borrowed(&EXTENDED_STREAM_PROPERTIES_OBJECT_GUID[..]),
+                        Cow::Borrowed(&input[389..405]),
+                    ],
+                })),
+                error_correction_object: None,
+                unknown_header_objects: vec![RawObject {
+                    guid: Cow::Borrowed(&input[499..515]),
+                    offset: 499,
+                    bytes: Cow::Borrowed(&input[499..737]),
+                }],
+                header_object_order: vec![
+                    Cow::Borrowed(&FILE_PROPERTIES_OBJECT_GUID[..]),
+                    Cow::Borrowed(&HEADER_EXTENSION_OBJECT_GUID[..]),
+                    Cow::Borrowed(&input[499..515]),
+                    Cow::Borrowed(&STREAM_PROPERTIES_OBJECT_GUID[..]),
+                    Cow::Borrowed(&STREAM_PROPERTIES_OBJECT_GUID[..]),
+                    Cow::Borrowed(&CODEC_LIST_OBJECT_GUID[..]),
+                ],
+                data_object: Some(Box::new(ans_data_object)),
+                simple_index_objects: vec![ans_simple_index_object],
+                unknown_objects: Vec::new(),
+                object_order: vec![
+                    Cow::Borrowed(&DATA_OBJECT_GUID[..]),
+                    Cow::Borrowed(&SIMPLE_INDEX_OBJECT_GUID[..]),
+                ],
             };
             assert_eq!(v, ans);
         }
@@ -449,7 +1456,7 @@ fn parse_asf_test2() {
     match asf_obj {
         Ok((_, v)) => {
             let ans_header_object = HeaderObject {
-                object_id: HEADER_OBJECT_GUID.to_vec(),
+                object_id: Cow::Borrowed(&HEADER_OBJECT_GUID[..]),
                 object_size: 5267,
                 num_header_objects: 7,
                 reserved_1: 1,
@@ -457,11 +1464,11 @@ fn parse_asf_test2() {
             };
 
             let ans_file_props_object = FilePropertiesObject {
-                object_id: FILE_PROPERTIES_OBJECT_GUID.to_vec(),
+                object_id: Cow::Borrowed(&FILE_PROPERTIES_OBJECT_GUID[..]),
                 object_size: 104,
-                file_id: vec![
+                file_id: Cow::Owned(vec![
                     43, 141, 105, 203, 0, 18, 13, 78, 169, 16, 243, 97, 122, 251, 50, 255,
-                ],
+                ]),
                 file_size: 1107099,
                 creation_date: 131299883009790000,
                 data_packets_count: 153,
@@ -476,50 +1483,50 @@ fn parse_asf_test2() {
 
             let ans_stream_props_objects = vec![
                 StreamPropertiesObject {
-                    object_id: STREAM_PROPERTIES_OBJECT_GUID.to_vec(),
+                    object_id: Cow::Borrowed(&STREAM_PROPERTIES_OBJECT_GUID[..]),
                     object_size: 114,
-                    stream_type: vec![
+                    stream_type: Cow::Owned(vec![
                         64, 158, 105, 248, 77, 91, 207, 17, 168, 253, 0, 128, 95, 92, 68, 43,
-                    ],
-                    error_correction_type: vec![
+                    ]),
+                    error_correction_type: Cow::Owned(vec![
                         80, 205, 195, 191, 143, 97, 207, 17, 139, 178, 0, 170, 0, 180, 226, 32,
-                    ],
+                    ]),
                     time_offset: 0,
                     type_specific_data_length: 28,
                     error_correction_data_length: 8,
                     flags: 1,
                     reserved: 32762,
-                    type_specific_data: vec![
+                    type_specific_data: Cow::Owned(vec![
                         97, 1, 2, 0, 68, 172, 0, 0, 69, 31, 0, 0, 207, 5, 16, 0, 10, 0, 0, 136, 0,
                         0, 15, 0, 0, 0, 0, 0,
-                    ],
-                    error_correction_data: vec![1, 207, 5, 207, 5, 1, 0, 0],
+                    ]),
+                    error_correction_data: Cow::Owned(vec![1, 207, 5, 207, 5, 1, 0, 0]),
                 },
                 StreamPropertiesObject {
-                    object_id: STREAM_PROPERTIES_OBJECT_GUID.to_vec(),
+                    object_id: Cow::Borrowed(&STREAM_PROPERTIES_OBJECT_GUID[..]),
                     object_size: 134,
-                    stream_type: vec![
+                    stream_type: Cow::Owned(vec![
                         192, 239, 25, 188, 77, 91, 207, 17, 168, 253, 0, 128, 95, 92, 68, 43,
-                    ],
-                    error_correction_type: vec![
+                    ]),
+                    error_correction_type: Cow::Owned(vec![
                         0, 87, 251, 32, 85, 91, 207, 17, 168, 253, 0, 128, 95, 92, 68, 43,
-                    ],
+                    ]),
                     time_offset: 0,
                     type_specific_data_length: 56,
                     error_correction_data_length: 0,
                     flags: 2,
                     reserved: 298803599,
-                    type_specific_data: vec![
+                    type_specific_data: Cow::Owned(vec![
                         128, 2, 0, 0, 104, 1, 0, 0, 2, 45, 0, 45, 0, 0, 0, 128, 2, 0, 0, 104, 1, 0,
                         0, 1, 0, 24, 0, 87, 77, 86, 51, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                         0, 0, 0, 0, 0, 0, 78, 137, 72, 1, 32,
-                    ],
-                    error_correction_data: vec![],
+                    ]),
+                    error_correction_data: Cow::Owned(vec![]),
                 },
             ];
 
             let ans_stream_bitrate_props_object = StreamBitratePropertiesObject {
-                object_id: STREAM_BITRATE_PROPERTIES_OBJECT_GUID.to_vec(),
+                object_id: Cow::Borrowed(&STREAM_BITRATE_PROPERTIES_OBJECT_GUID[..]),
                 object_size: 38,
                 bitrate_records_count: 2,
                 bitrate_records: vec![
@@ -534,12 +1541,243 @@ fn parse_asf_test2() {
                 ],
             };
 
+            let ans_codec_list_object = CodecListObject {
+                object_id: Cow::Borrowed(&CODEC_LIST_OBJECT_GUID[..]),
+                object_size: 244,
+                reserved: Cow::Owned(vec![
+                    65, 82, 209, 134, 29, 49, 208, 17, 163, 164, 0, 160, 201, 3, 72, 246,
+                ]),
+                codec_entries_count: 2,
+                codec_entries: vec![
+                    CodecEntry {
+                        codec_type: 2,
+                        codec_name_length: 24,
+                        codec_name: Cow::Owned(utf16::encode("Windows Media Audio 9.2\0")),
+                        codec_description_length: 42,
+                        codec_description: Cow::Owned(utf16::encode(
+                            " 64 kbps, 44 kHz, stereo (A/V) 1-pass CBR\0",
+                        )),
+                        codec_information_length: 2,
+                        codec_information: Cow::Owned(vec![97, 1]),
+                    },
+                    CodecEntry {
+                        codec_type: 1,
+                        codec_name_length: 22,
+                        codec_name: Cow::Owned(utf16::encode("Windows Media Video 9\0")),
+                        codec_description_length: 1,
+                        codec_description: Cow::Owned(utf16::encode("\0")),
+                        codec_information_length: 4,
+                        codec_information: Cow::Owned(vec![87, 77, 86, 51]),
+                    },
+                ],
+            };
+
+            let ans_data_object = DataObject {
+                object_id: Cow::Borrowed(&DATA_OBJECT_GUID[..]),
+                object_size: 1101650,
+                file_id: Cow::Owned(vec![
+                    43, 141, 105, 203, 0, 18, 13, 78, 169, 16, 243, 97, 122, 251, 50, 255,
+                ]),
+                total_data_packets: 153,
+                reserved: 257,
+                data_packets: Cow::Borrowed(&input[5317..1106917]),
+            };
+
+            let ans_simple_index_object = SimpleIndexObject {
+                object_id: Cow::Borrowed(&SIMPLE_INDEX_OBJECT_GUID[..]),
+                object_size: 182,
+                file_id: Cow::Owned(vec![
+                    43, 141, 105, 203, 0, 18, 13, 78, 169, 16, 243, 97, 122, 251, 50, 255,
+                ]),
+                index_entry_time_interval: 10000000,
+                maximum_packet_count: 2,
+                index_entries_count: 21,
+                index_entries: vec![
+                    (0, 1),
+                    (0, 1),
+                    (0, 1),
+                    (0, 1),
+                    (0, 1),
+                    (0, 1),
+                    (0, 2),
+                    (20, 1),
+                    (33, 2),
+                    (44, 2),
+                    (44, 2),
+                    (68, 1),
+                    (68, 1),
+                    (68, 1),
+                    (68, 1),
+                    (68, 1),
+                    (68, 1),
+                    (126, 1),
+                    (126, 1),
+                    (126, 1),
+                    (126, 1),
+                ]
+                .into_iter()
+                .map(|(packet_number, packet_count)| IndexEntry {
+                    packet_number,
+                    packet_count,
+                })
+                .collect(),
+            };
+
             let ans = ASF {
                 header_object: ans_header_object,
                 file_props_object: Some(Box::new(ans_file_props_object)),
                 stream_props_objects: ans_stream_props_objects,
                 stream_bitrate_props_object: Some(Box::new(ans_stream_bitrate_props_object)),
-                data_object: None,
+                codec_list_object: Some(Box::new(ans_codec_list_object)),
+                marker_object: None,
+                script_command_object: None,
+                content_encryption_object: None,
+                extended_content_encryption_object: None,
+                digital_signature_object: None,
+                content_branding_object: None,
+                bitrate_mutual_exclusion_objects: Vec::new(),
+                header_extension_object: Some(Box::new(HeaderExtensionObject {
+                    object_id: Cow::Borrowed(&HEADER_EXTENSION_OBJECT_GUID[..]),
+                    object_size: 4437,
+                    reserved_field_1: Cow::Owned(vec![
+                        17, 210, 211, 171, 186, 169, 207, 17, 142, 230, 0, 192, 12, 32, 83, 101,
+                    ]),
+                    reserved_field_2: 6,
+                    header_extension_data_size: 4391,
+                    advanced_mutual_exclusion_objects: Vec::new(),
+                    group_mutual_exclusion_objects: Vec::new(),
+                    stream_prioritization_object: None,
+                    bandwidth_sharing_objects: Vec::new(),
+                    language_list_object: Some(Box::new(LanguageListObject {
+                        object_id: Cow::Borrowed(&LANGUAGE_LIST_OBJECT_GUID[..]),
+                        object_size: 33,
+                        language_id_records_count: 1,
+                        language_id_records: vec![LanguageIdRecord {
+                            language_id_length: 6,
+                            language_id: Cow::Owned(utf16::encode("ja\0")),
+                        }],
+                    })),
+                    extended_stream_props_objects: vec![
+                        ExtendedStreamPropertiesObject {
+                            object_id: Cow::Borrowed(&EXTENDED_STREAM_PROPERTIES_OBJECT_GUID[..]),
+                            object_size: 88,
+                            start_time: 0,
+                            end_time: 0,
+                            data_bitrate: 64040,
+                            buffer_size: 1579,
+                            initial_buffer_fullness: 0,
+                            alternate_data_bitrate: 64040,
+                            alternate_buffer_size: 1579,
+                            alternate_initial_buffer_fullness: 0,
+                            maximum_object_size: 1487,
+                            flags: 2,
+                            stream_number: 1,
+                            stream_language_id_index: 0,
+                            average_time_per_frame: 1186794,
+                            stream_name_count: 0,
+                            payload_extension_system_count: 0,
+                            stream_names: Vec::new(),
+                            payload_extension_systems: Vec::new(),
+                            stream_props_object: None,
+                        },
+                        ExtendedStreamPropertiesObject {
+                            object_id: Cow::Borrowed(&EXTENDED_STREAM_PROPERTIES_OBJECT_GUID[..]),
+                            object_size: 132,
+                            start_time: 0,
+                            end_time: 0,
+                            data_bitrate: 512000,
+                            buffer_size: 5000,
+                            initial_buffer_fullness: 0,
+                            alternate_data_bitrate: 512000,
+                            alternate_buffer_size: 5000,
+                            alternate_initial_buffer_fullness: 0,
+                            maximum_object_size: 9513,
+                            flags: 2,
+                            stream_number: 2,
+                            stream_language_id_index: 0,
+                            average_time_per_frame: 333333,
+                            stream_name_count: 0,
+                            payload_extension_system_count: 2,
+                            stream_names: Vec::new(),
+                            payload_extension_systems: vec![
+                                PayloadExtensionSystem {
+                                    extension_system_id: Cow::Owned(vec![
+                                        84, 229, 30, 27, 234, 249, 200, 75, 130, 26, 55, 107, 116,
+                                        228, 196, 184,
+                                    ]),
+                                    extension_data_size: 2,
+                                    extension_system_info_length: 0,
+                                    extension_system_info: Cow::Owned(vec![]),
+                                },
+                                PayloadExtensionSystem {
+                                    extension_system_id: Cow::Owned(vec![
+                                        80, 148, 189, 198, 127, 134, 7, 73, 131, 163, 199, 121, 33,
+                                        183, 51, 173,
+                                    ]),
+                                    extension_data_size: 2,
+                                    extension_system_info_length: 0,
+                                    extension_system_info: Cow::Owned(vec![]),
+                                },
+                            ],
+                            stream_props_object: None,
+                        },
+                    ],
+                    metadata_library_object: None,
+                    compatibility_object: Some(Box::new(CompatibilityObject {
+                        object_id: Cow::Borrowed(&COMPATIBILITY_OBJECT_GUID[..]),
+                        object_size: 26,
+                        profile: 2,
+                        mode: 1,
+                    })),
+                    unknown_objects: vec![
+                        RawObject {
+                            guid: Cow::Borrowed(&input[239..255]),
+                            offset: 239,
+                            bytes: Cow::Borrowed(&input[239..463]),
+                        },
+                        RawObject {
+                            guid: Cow::Borrowed(&input[463..479]),
+                            offset: 463,
+                            bytes: Cow::Borrowed(&input[463..4313]),
+                        },
+                        RawObject {
+                            guid: Cow::Borrowed(&input[4533..4549]),
+                            offset: 4533,
+                            bytes: Cow::Borrowed(&input[4533..4571]),
+                        },
+                    ],
+                    object_order: vec![
+                        Cow::Borrowed(&LANGUAGE_LIST_OBJECT_GUID[..]),
+                        Cow::Borrowed(&COMPATIBILITY_OBJECT_GUID[..]),
+                        Cow::Borrowed(&input[239..255]),
+                        Cow::Borrowed(&input[463..479]),
+                        Cow::Borrowed(&EXTENDED_STREAM_PROPERTIES_OBJECT_GUID[..]),
+                        Cow::Borrowed(&EXTENDED_STREAM_PROPERTIES_OBJECT_GUID[..]),
+                        Cow::Borrowed(&input[4533..4549]),
+                    ],
+                })),
+                error_correction_object: None,
+                unknown_header_objects: vec![RawObject {
+                    guid: Cow::Borrowed(&input[4571..4587]),
+                    offset: 4571,
+                    bytes: Cow::Borrowed(&input[4571..4737]),
+                }],
+                header_object_order: vec![
+                    Cow::Borrowed(&FILE_PROPERTIES_OBJECT_GUID[..]),
+                    Cow::Borrowed(&HEADER_EXTENSION_OBJECT_GUID[..]),
+                    Cow::Borrowed(&input[4571..4587]),
+                    Cow::Borrowed(&CODEC_LIST_OBJECT_GUID[..]),
+                    Cow::Borrowed(&STREAM_PROPERTIES_OBJECT_GUID[..]),
+                    Cow::Borrowed(&STREAM_PROPERTIES_OBJECT_GUID[..]),
+                    Cow::Borrowed(&STREAM_BITRATE_PROPERTIES_OBJECT_GUID[..]),
+                ],
+                data_object: Some(Box::new(ans_data_object)),
+                simple_index_objects: vec![ans_simple_index_object],
+                unknown_objects: Vec::new(),
+                object_order: vec![
+                    Cow::Borrowed(&DATA_OBJECT_GUID[..]),
+                    Cow::Borrowed(&SIMPLE_INDEX_OBJECT_GUID[..]),
+                ],
             };
             assert_eq!(v, ans);
         }
@@ -554,3 +1792,92 @@ fn parse_asf_test2() {
         }
     }
 }
+
+#[test]
+fn file_props_object_time_test() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = parse_asf(input).unwrap();
+    let mut file_props_object = asf.file_props_object.unwrap();
+
+    assert!(!file_props_object.is_broadcast());
+    assert!(file_props_object.is_seekable());
+    assert_eq!(
+        file_props_object.creation_time(),
+        UNIX_EPOCH + Duration::new(1_485_514_700, 979_000_000)
+    );
+    assert_eq!(file_props_object.known_file_size(), Some(1107099));
+    assert_eq!(file_props_object.known_data_packets_count(), Some(153));
+    assert_eq!(
+        file_props_object.play_time(),
+        Some(Duration::from_millis(19399))
+    );
+    assert_eq!(
+        file_props_object.send_time(),
+        Some(Duration::from_millis(17822))
+    );
+    assert_eq!(file_props_object.preroll_time(), Duration::from_secs(5));
+    assert_eq!(
+        file_props_object.playback_duration(),
+        Some(Duration::from_millis(14399))
+    );
+
+    file_props_object.flags |= FILE_PROPERTIES_FLAG_BROADCAST;
+    assert!(file_props_object.is_broadcast());
+    assert_eq!(file_props_object.known_file_size(), None);
+    assert_eq!(file_props_object.known_data_packets_count(), None);
+    assert_eq!(file_props_object.play_time(), None);
+    assert_eq!(file_props_object.send_time(), None);
+    assert_eq!(file_props_object.playback_duration(), None);
+}
+
+#[test]
+fn validate_streams_test() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, mut asf) = parse_asf(input).unwrap();
+
+    let streams = asf
+        .streams_with_bitrates()
+        .into_iter()
+        .map(|(stream_props_object, bitrate_record)| {
+            (
+                stream_props_object.stream_number(),
+                stream_props_object.is_encrypted(),
+                bitrate_record.map(|v| v.average_bitrate),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        streams,
+        vec![(1, false, Some(65733)), (2, false, Some(519765))]
+    );
+    assert!(asf.validate_streams().is_ok());
+
+    asf.stream_props_objects[1].flags = 0x8001;
+    assert!(asf.stream_props_objects[1].is_encrypted());
+    match asf.validate_streams() {
+        Err(Error::DuplicateStreamNumber(1)) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+
+    asf.stream_props_objects[1].flags = 0x8000;
+    match asf.validate_streams() {
+        Err(Error::InvalidStreamNumber(0)) => {}
+        v => panic!("unexpected result: {:?}", v),
+    }
+}
+
+#[test]
+fn parse_asf_into_owned_test() {
+    fn parse_owned(input: Vec<u8>) -> ASF<'static> {
+        parse_asf(&input).unwrap().1.into_owned()
+    }
+
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, borrowed) = parse_asf(input).unwrap();
+    let owned = parse_owned(input.to_vec());
+    assert_eq!(owned, borrowed);
+    match owned.stream_props_objects[0].type_specific_data {
+        Cow::Owned(_) => {}
+        Cow::Borrowed(_) => panic!("type_specific_data is still borrowed"),
+    }
+}
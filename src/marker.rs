@@ -0,0 +1,340 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::time::Duration;
+
+use nom::{le_u16, le_u32, le_u64, rest};
+
+use crate::utf16;
+use crate::{duration_from_100ns, ASF, MARKER_OBJECT_GUID};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MarkerEntry<'a> {
+    // Data Object のパケット先頭からのバイトオフセット
+    pub offset: u64,
+    // 100ナノ秒単位. プリロールを含む
+    pub presentation_time: u64,
+    // Send Time 以降のエントリのサイズ
+    pub entry_length: u16,
+    // ミリ秒単位
+    pub send_time: u32,
+    // 予約領域
+    pub flags: u32,
+    // Marker Description の文字数. 終端のヌル文字を含む
+    pub marker_description_length: u32,
+    // UTF-16LE
+    pub marker_description: Cow<'a, [u8]>,
+}
+
+impl<'a> MarkerEntry<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> MarkerEntry<'static> {
+        MarkerEntry {
+            offset: self.offset,
+            presentation_time: self.presentation_time,
+            entry_length: self.entry_length,
+            send_time: self.send_time,
+            flags: self.flags,
+            marker_description_length: self.marker_description_length,
+            marker_description: Cow::Owned(self.marker_description.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MarkerObject<'a> {
+    // F487CD01-A951-11CF-8EE6-00C00C205365
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // 4CFEDB20-75F6-11CF-9C0F-00A0C90349CB
+    pub reserved: Cow<'a, [u8]>,
+    // Markers の総数
+    pub markers_count: u32,
+    // 予約領域
+    pub reserved_2: u16,
+    // Name のバイト数
+    pub name_length: u16,
+    // UTF-16LE
+    pub name: Cow<'a, [u8]>,
+    pub markers: Vec<MarkerEntry<'a>>,
+}
+
+impl<'a> MarkerObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> MarkerObject<'static> {
+        MarkerObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            reserved: Cow::Owned(self.reserved.into_owned()),
+            markers_count: self.markers_count,
+            reserved_2: self.reserved_2,
+            name_length: self.name_length,
+            name: Cow::Owned(self.name.into_owned()),
+            markers: self
+                .markers
+                .into_iter()
+                .map(MarkerEntry::into_owned)
+                .collect(),
+        }
+    }
+}
+
+// プリロールを除いた時間に変換したマーカー
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Marker {
+    // Marker Description
+    pub name: String,
+    // プリロールを除いた表示時間
+    pub presentation_time: Duration,
+    // 送信時間
+    pub send_time: Duration,
+    // Data Object のパケット先頭からのバイトオフセット
+    pub offset: u64,
+}
+
+named!(
+    parse_marker_entry<MarkerEntry>,
+    do_parse!(
+        offset: le_u64
+            >> presentation_time: le_u64
+            >> entry_length: le_u16
+            >> entry:
+                flat_map!(
+                    take!(entry_length),
+                    do_parse!(
+                        send_time: le_u32
+                            >> flags: le_u32
+                            >> marker_description_length: le_u32
+                            >> marker_description: take!(marker_description_length as usize * 2)
+                            >> _padding: rest
+                            >> ((send_time, flags, marker_description_length, marker_description))
+                    )
+                )
+            >> (MarkerEntry {
+                offset,
+                presentation_time,
+                entry_length,
+                send_time: entry.0,
+                flags: entry.1,
+                marker_description_length: entry.2,
+                marker_description: Cow::Borrowed(entry.3),
+            })
+    )
+);
+
+named!(
+    pub parse_marker_object<MarkerObject>,
+    do_parse!(
+        object_size: le_u64
+            >> reserved: take!(16)
+            >> markers_count: le_u32
+            >> reserved_2: le_u16
+            >> name_length: le_u16
+            >> name: take!(name_length)
+            >> markers:
+                many_m_n!(
+                    markers_count as usize,
+                    markers_count as usize,
+                    parse_marker_entry
+                )
+            >> (MarkerObject {
+                object_id: Cow::Borrowed(&MARKER_OBJECT_GUID[..]),
+                object_size,
+                reserved: Cow::Borrowed(reserved),
+                markers_count,
+                reserved_2,
+                name_length,
+                name: Cow::Borrowed(name),
+                markers,
+            })
+    )
+);
+
+impl<'a> ASF<'a> {
+    // Marker Object のマーカーを表示時間順に返す
+    pub fn markers(&self) -> Vec<Marker> {
+        let marker_object = match self.marker_object {
+            Some(ref marker_object) => marker_object,
+            None => return Vec::new(),
+        };
+        let preroll = self
+            .file_props_object
+            .as_ref()
+            .map(|v| v.preroll_time())
+            .unwrap_or_default();
+
+        let mut markers = marker_object
+            .markers
+            .iter()
+            .map(|v| Marker {
+                name: utf16::decode(&v.marker_description),
+                presentation_time: duration_from_100ns(v.presentation_time)
+                    .checked_sub(preroll)
+                    .unwrap_or_default(),
+                send_time: Duration::from_millis(u64::from(v.send_time)),
+                offset: v.offset,
+            })
+            .collect::<Vec<_>>();
+        markers.sort_by_key(|v| v.presentation_time);
+        markers
+    }
+}
+
+// 各チャプターの終了時間. 次のマーカーの開始時間, 最後のチャプターは duration
+fn chapter_ends(markers: &[Marker], duration: Duration) -> Vec<Duration> {
+    markers
+        .iter()
+        .enumerate()
+        .map(|(i, marker)| match markers.get(i + 1) {
+            Some(next) => next.presentation_time,
+            None => duration.max(marker.presentation_time),
+        })
+        .collect()
+}
+
+// FFmpeg の FFMETADATA 形式のチャプターに変換する
+pub fn to_ffmetadata(markers: &[Marker], duration: Duration) -> String {
+    let mut s = String::from(";FFMETADATA1\n");
+    for (marker, end) in markers.iter().zip(chapter_ends(markers, duration)) {
+        let mut title = String::new();
+        for c in marker.name.chars() {
+            if let '=' | ';' | '#' | '\\' | '\n' = c {
+                title.push('\\');
+            }
+            title.push(c);
+        }
+        let _ = write!(
+            s,
+            "\n[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            marker.presentation_time.as_millis(),
+            end.as_millis(),
+            title
+        );
+    }
+    s
+}
+
+// "00:01:02.500000000" のように表示する
+fn matroska_timestamp(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}.{:09}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        time.subsec_nanos()
+    )
+}
+
+// Matroska のチャプター XML に変換する
+pub fn to_matroska_chapters(markers: &[Marker], duration: Duration) -> String {
+    let mut s = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE Chapters SYSTEM \"matroskachapters.dtd\">\n\
+         <Chapters>\n  <EditionEntry>\n",
+    );
+    for (marker, end) in markers.iter().zip(chapter_ends(markers, duration)) {
+        let mut title = String::new();
+        for c in marker.name.chars() {
+            match c {
+                '&' => title.push_str("&amp;"),
+                '<' => title.push_str("&lt;"),
+                '>' => title.push_str("&gt;"),
+                '"' => title.push_str("&quot;"),
+                c => title.push(c),
+            }
+        }
+        let _ = write!(
+            s,
+            "    <ChapterAtom>\n      <ChapterTimeStart>{}</ChapterTimeStart>\n      \
+             <ChapterTimeEnd>{}</ChapterTimeEnd>\n      <ChapterDisplay>\n        \
+             <ChapterString>{}</ChapterString>\n      </ChapterDisplay>\n    </ChapterAtom>\n",
+            matroska_timestamp(marker.presentation_time),
+            matroska_timestamp(end),
+            title
+        );
+    }
+    s.push_str("  </EditionEntry>\n</Chapters>\n");
+    s
+}
+
+#[cfg(test)]
+fn marker_object_bytes(markers: &[(u64, &str)]) -> Vec<u8> {
+    let mut input = Vec::new();
+    input.extend_from_slice(&[0; 8]);
+    input.extend_from_slice(&[
+        0x20, 0xDB, 0xFE, 0x4C, 0xF6, 0x75, 0xCF, 0x11, 0x9C, 0x0F, 0x00, 0xA0, 0xC9, 0x03, 0x49,
+        0xCB,
+    ]);
+    input.extend_from_slice(&(markers.len() as u32).to_le_bytes());
+    input.extend_from_slice(&[0, 0, 0, 0]);
+    for (i, (presentation_time, name)) in markers.iter().enumerate() {
+        let description = utf16::encode(&format!("{}\0", name));
+        input.extend_from_slice(&(i as u64 * 3200).to_le_bytes());
+        input.extend_from_slice(&presentation_time.to_le_bytes());
+        input.extend_from_slice(&(12 + description.len() as u16).to_le_bytes());
+        input.extend_from_slice(&((presentation_time / 10_000) as u32).to_le_bytes());
+        input.extend_from_slice(&0u32.to_le_bytes());
+        input.extend_from_slice(&(description.len() as u32 / 2).to_le_bytes());
+        input.extend_from_slice(&description);
+    }
+    let object_size = input.len() as u64 + 16;
+    input[..8].copy_from_slice(&object_size.to_le_bytes());
+    input
+}
+
+#[test]
+fn parse_marker_object_test() {
+    let input = marker_object_bytes(&[(31_000_000, "Intro"), (81_000_000, "Part <1>")]);
+    let (remain, marker_object) = parse_marker_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(marker_object.markers_count, 2);
+    assert_eq!(marker_object.markers[1].offset, 3200);
+    assert_eq!(marker_object.markers[1].send_time, 8100);
+    assert_eq!(marker_object.markers[1].marker_description_length, 9);
+
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    asf.marker_object = Some(Box::new(marker_object.into_owned()));
+    let markers = asf.markers();
+    let preroll = asf.file_props_object.as_ref().unwrap().preroll_time();
+
+    assert_eq!(markers.len(), 2);
+    assert_eq!(markers[0].name, "Intro");
+    assert_eq!(
+        markers[1].presentation_time,
+        Duration::from_millis(8100) - preroll
+    );
+}
+
+#[test]
+fn chapters_export_test() {
+    let markers = vec![
+        Marker {
+            name: "Intro".to_string(),
+            presentation_time: Duration::from_millis(0),
+            send_time: Duration::from_millis(0),
+            offset: 0,
+        },
+        Marker {
+            name: "A=B; <C>".to_string(),
+            presentation_time: Duration::from_millis(62_500),
+            send_time: Duration::from_millis(62_000),
+            offset: 7200,
+        },
+    ];
+    let duration = Duration::from_secs(3700);
+
+    assert_eq!(
+        to_ffmetadata(&markers, duration),
+        ";FFMETADATA1\n\
+         \n[CHAPTER]\nTIMEBASE=1/1000\nSTART=0\nEND=62500\ntitle=Intro\n\
+         \n[CHAPTER]\nTIMEBASE=1/1000\nSTART=62500\nEND=3700000\ntitle=A\\=B\\; <C>\n"
+    );
+
+    let xml = to_matroska_chapters(&markers, duration);
+    assert!(xml.contains("<ChapterTimeStart>00:01:02.500000000</ChapterTimeStart>"));
+    assert!(xml.contains("<ChapterTimeEnd>01:01:40.000000000</ChapterTimeEnd>"));
+    assert!(xml.contains("<ChapterString>A=B; &lt;C&gt;</ChapterString>"));
+}
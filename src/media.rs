@@ -0,0 +1,287 @@
+use std::collections::VecDeque;
+
+use crate::packet::{DataPackets, Payload};
+use crate::spread::AudioSpread;
+use crate::{Error, Result, StreamPropertiesObject};
+
+// 組み立て途中のメディアオブジェクトに最初に確保するサイズの上限.
+// Media Object Size は入力のまま信用できないので、それ以上は追加に合わせて伸ばす
+const MAX_INITIAL_CAPACITY: usize = 1024 * 1024;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MediaObject {
+    // ストリーム番号
+    pub stream_number: u8,
+    // メディアオブジェクトの番号
+    pub media_object_number: u32,
+    // 表示時間(ミリ秒)
+    pub presentation_time: u32,
+    // キーフレームかどうか
+    pub key_frame: bool,
+    // 実データ
+    pub data: Vec<u8>,
+}
+
+// 組み立て途中のメディアオブジェクト
+#[derive(Clone)]
+struct Fragment {
+    object: MediaObject,
+    size: usize,
+}
+
+// ペイロードを受け取り、メディアオブジェクトを組み立てる
+#[derive(Default, Clone)]
+pub struct MediaObjectAssembler {
+    // ストリーム番号ごとの組み立て途中のメディアオブジェクト
+    fragments: Vec<(u8, Fragment)>,
+    // Audio Spread を使うストリームの番号と Error Correction Data
+    audio_spreads: Vec<(u8, AudioSpread<'static>)>,
+}
+
+impl MediaObjectAssembler {
+    pub fn new() -> MediaObjectAssembler {
+        MediaObjectAssembler::default()
+    }
+
+    // Audio Spread を使うストリームのメディアオブジェクトを元の順序に戻す
+    pub fn for_streams(stream_props_objects: &[StreamPropertiesObject]) -> MediaObjectAssembler {
+        let audio_spreads = stream_props_objects
+            .iter()
+            .filter_map(|stream_props_object| {
+                let audio_spread = stream_props_object.audio_spread()?;
+                Some((
+                    stream_props_object.stream_number(),
+                    audio_spread.into_owned(),
+                ))
+            })
+            .collect();
+        MediaObjectAssembler {
+            fragments: Vec::new(),
+            audio_spreads,
+        }
+    }
+
+    // 完成したメディアオブジェクトを out に追加する
+    fn emit(&self, mut object: MediaObject, out: &mut VecDeque<MediaObject>) {
+        if let Some((_, audio_spread)) = self
+            .audio_spreads
+            .iter()
+            .find(|&&(stream_number, _)| stream_number == object.stream_number)
+        {
+            object.data = audio_spread.descramble(&object.data);
+        }
+        out.push_back(object);
+    }
+
+    // ペイロードを追加し、完成したメディアオブジェクトを out に追加する
+    pub fn push(&mut self, payload: &Payload, out: &mut VecDeque<MediaObject>) {
+        if payload.is_compressed() {
+            // サブペイロードはそれぞれが1つのメディアオブジェクト
+            let delta = u32::from(payload.replicated_data[0]);
+            for (i, sub_payload) in payload.sub_payloads().enumerate() {
+                let object = MediaObject {
+                    stream_number: payload.stream_number,
+                    media_object_number: payload.media_object_number.wrapping_add(i as u32),
+                    presentation_time: payload
+                        .offset_into_media_object
+                        .wrapping_add(delta.wrapping_mul(i as u32)),
+                    key_frame: payload.key_frame,
+                    data: sub_payload.to_vec(),
+                };
+                self.emit(object, out);
+            }
+            return;
+        }
+
+        let (size, presentation_time) =
+            match (payload.media_object_size(), payload.presentation_time()) {
+                (Some(size), Some(presentation_time)) => (size as usize, presentation_time),
+                _ => {
+                    // Replicated Data が無い場合はペイロードをそのままメディアオブジェクトとする
+                    let object = MediaObject {
+                        stream_number: payload.stream_number,
+                        media_object_number: payload.media_object_number,
+                        presentation_time: 0,
+                        key_frame: payload.key_frame,
+                        data: payload.payload_data.to_vec(),
+                    };
+                    self.emit(object, out);
+                    return;
+                }
+            };
+
+        let pos = self
+            .fragments
+            .iter()
+            .position(|&(stream_number, _)| stream_number == payload.stream_number);
+        let offset = payload.offset_into_media_object as usize;
+        let pos = if offset == 0 {
+            if let Some(pos) = pos {
+                debug!(
+                    "drop incomplete media object: stream={} number={}",
+                    payload.stream_number, self.fragments[pos].1.object.media_object_number
+                );
+                self.fragments.swap_remove(pos);
+            }
+            self.fragments.push((
+                payload.stream_number,
+                Fragment {
+                    object: MediaObject {
+                        stream_number: payload.stream_number,
+                        media_object_number: payload.media_object_number,
+                        presentation_time,
+                        key_frame: payload.key_frame,
+                        data: Vec::with_capacity(size.min(MAX_INITIAL_CAPACITY)),
+                    },
+                    size,
+                },
+            ));
+            self.fragments.len() - 1
+        } else {
+            match pos {
+                Some(pos)
+                    if self.fragments[pos].1.object.media_object_number
+                        == payload.media_object_number
+                        && self.fragments[pos].1.object.data.len() == offset =>
+                {
+                    pos
+                }
+                _ => {
+                    debug!(
+                        "drop orphan payload: stream={} number={} offset={}",
+                        payload.stream_number, payload.media_object_number, offset
+                    );
+                    if let Some(pos) = pos {
+                        self.fragments.swap_remove(pos);
+                    }
+                    return;
+                }
+            }
+        };
+
+        let done = {
+            let fragment = &mut self.fragments[pos].1;
+            fragment
+                .object
+                .data
+                .extend_from_slice(&payload.payload_data);
+            fragment.object.data.len() >= fragment.size
+        };
+        if done {
+            let (_, fragment) = self.fragments.swap_remove(pos);
+            self.emit(fragment.object, out);
+        }
+    }
+}
+
+// Data Object のパケットからメディアオブジェクトを順に組み立てる
+pub struct MediaObjects<'a> {
+    packets: DataPackets<'a>,
+    assembler: MediaObjectAssembler,
+    queue: VecDeque<MediaObject>,
+    // DRM で保護されている場合は最初に Error::Protected を返して終了する
+    protected: bool,
+}
+
+impl<'a> MediaObjects<'a> {
+    pub fn new(packets: DataPackets<'a>, assembler: MediaObjectAssembler) -> MediaObjects<'a> {
+        MediaObjects {
+            packets,
+            assembler,
+            queue: VecDeque::new(),
+            protected: false,
+        }
+    }
+
+    // 暗号化されたデータをメディアオブジェクトとして返さないようにする
+    pub fn protected(mut self, protected: bool) -> MediaObjects<'a> {
+        self.protected = protected;
+        self
+    }
+}
+
+impl<'a> Iterator for MediaObjects<'a> {
+    type Item = Result<MediaObject>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.protected {
+            self.protected = false;
+            self.packets = DataPackets::new(&[], 0);
+            return Some(Err(Error::Protected));
+        }
+        loop {
+            if let Some(object) = self.queue.pop_front() {
+                return Some(Ok(object));
+            }
+            match self.packets.next()? {
+                Ok(packet) => {
+                    for payload in &packet.payloads {
+                        self.assembler.push(payload, &mut self.queue);
+                    }
+                }
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[test]
+fn media_objects_test1() {
+    let input = include_bytes!("../assets/320x180_10fps.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let objects = asf
+        .media_objects()
+        .unwrap()
+        .collect::<Result<Vec<_>>>()
+        .unwrap();
+
+    let video = objects
+        .iter()
+        .filter(|object| object.stream_number == 1)
+        .collect::<Vec<_>>();
+    assert_eq!(video.len(), 10);
+    assert!(video[0].key_frame);
+    assert!(objects
+        .iter()
+        .any(|object| object.stream_number == 2 && object.data.len() == 185));
+}
+
+#[test]
+fn media_object_assembler_compressed_test() {
+    use std::borrow::Cow;
+
+    let mut assembler = MediaObjectAssembler {
+        fragments: Vec::new(),
+        audio_spreads: vec![(
+            1,
+            AudioSpread {
+                span: 2,
+                virtual_packet_length: 4,
+                virtual_chunk_length: 2,
+                silence_data_length: 0,
+                silence_data: Cow::Borrowed(&[]),
+            },
+        )],
+    };
+    // 2つのサブペイロードがそれぞれ Audio Spread で並び替えられている
+    let payload_data = vec![8, 0, 1, 4, 5, 2, 3, 6, 7, 8, 10, 11, 14, 15, 12, 13, 16, 17];
+    let payload = Payload {
+        stream_number: 1,
+        key_frame: true,
+        media_object_number: 5,
+        offset_into_media_object: 3000,
+        replicated_data_length: 1,
+        replicated_data: Cow::Borrowed(&[100]),
+        payload_length: payload_data.len() as u32,
+        payload_data: Cow::Owned(payload_data),
+    };
+    let mut out = VecDeque::new();
+    assembler.push(&payload, &mut out);
+
+    assert_eq!(out.len(), 2);
+    assert_eq!(out[0].presentation_time, 3000);
+    assert_eq!(out[0].data, vec![0, 1, 2, 3, 4, 5, 6, 7]);
+    assert_eq!(out[1].media_object_number, 6);
+    assert_eq!(out[1].presentation_time, 3100);
+    assert_eq!(out[1].data, vec![10, 11, 12, 13, 14, 15, 16, 17]);
+}
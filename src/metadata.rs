@@ -0,0 +1,176 @@
+use std::borrow::Cow;
+
+use nom::{le_u16, le_u32, le_u64};
+
+use crate::utf16;
+use crate::METADATA_LIBRARY_OBJECT_GUID;
+
+// Data Type
+pub const DATA_TYPE_UNICODE_STRING: u16 = 0x0000;
+pub const DATA_TYPE_BYTE_ARRAY: u16 = 0x0001;
+pub const DATA_TYPE_BOOL: u16 = 0x0002;
+pub const DATA_TYPE_DWORD: u16 = 0x0003;
+pub const DATA_TYPE_QWORD: u16 = 0x0004;
+pub const DATA_TYPE_WORD: u16 = 0x0005;
+pub const DATA_TYPE_GUID: u16 = 0x0006;
+
+// Data Type に従って解析した属性の値
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum AttributeValue {
+    String(String),
+    Bytes(Vec<u8>),
+    Bool(bool),
+    Dword(u32),
+    Qword(u64),
+    Word(u16),
+    Guid(Vec<u8>),
+}
+
+impl AttributeValue {
+    // data_type に従って data を解析する. サイズが合わない場合は Bytes になる
+    pub fn parse(data_type: u16, data: &[u8]) -> AttributeValue {
+        let mut le = [0u8; 8];
+        let n = data.len().min(8);
+        le[..n].copy_from_slice(&data[..n]);
+        let value = u64::from_le_bytes(le);
+        match (data_type, data.len()) {
+            (DATA_TYPE_UNICODE_STRING, _) => AttributeValue::String(utf16::decode(data)),
+            // Metadata Library Object の BOOL は2bytes, Extended Content Description Object は4bytes
+            (DATA_TYPE_BOOL, 2) | (DATA_TYPE_BOOL, 4) => AttributeValue::Bool(value != 0),
+            (DATA_TYPE_DWORD, 4) => AttributeValue::Dword(value as u32),
+            (DATA_TYPE_QWORD, 8) => AttributeValue::Qword(value),
+            (DATA_TYPE_WORD, 2) => AttributeValue::Word(value as u16),
+            (DATA_TYPE_GUID, 16) => AttributeValue::Guid(data.to_vec()),
+            _ => AttributeValue::Bytes(data.to_vec()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MetadataLibraryRecord<'a> {
+    // Language List Object のインデックス
+    pub language_list_index: u16,
+    // ストリーム番号. ファイル全体の場合は 0
+    pub stream_number: u16,
+    // Name のバイト数
+    pub name_length: u16,
+    // Unicode String(0), Byte Array(1), BOOL(2), DWORD(3), QWORD(4), WORD(5), GUID(6)
+    pub data_type: u16,
+    // Data のバイト数
+    pub data_length: u32,
+    // UTF-16LE
+    pub name: Cow<'a, [u8]>,
+    pub data: Cow<'a, [u8]>,
+}
+
+impl<'a> MetadataLibraryRecord<'a> {
+    pub fn name(&self) -> String {
+        utf16::decode(&self.name)
+    }
+
+    pub fn value(&self) -> AttributeValue {
+        AttributeValue::parse(self.data_type, &self.data)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> MetadataLibraryRecord<'static> {
+        MetadataLibraryRecord {
+            language_list_index: self.language_list_index,
+            stream_number: self.stream_number,
+            name_length: self.name_length,
+            data_type: self.data_type,
+            data_length: self.data_length,
+            name: Cow::Owned(self.name.into_owned()),
+            data: Cow::Owned(self.data.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct MetadataLibraryObject<'a> {
+    // 44231C94-9498-49D1-A141-1D134E457054
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // Description Records の総数
+    pub description_records_count: u16,
+    pub description_records: Vec<MetadataLibraryRecord<'a>>,
+}
+
+impl<'a> MetadataLibraryObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> MetadataLibraryObject<'static> {
+        MetadataLibraryObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            description_records_count: self.description_records_count,
+            description_records: self
+                .description_records
+                .into_iter()
+                .map(MetadataLibraryRecord::into_owned)
+                .collect(),
+        }
+    }
+}
+
+named!(
+    parse_metadata_library_record<MetadataLibraryRecord>,
+    do_parse!(
+        language_list_index: le_u16
+            >> stream_number: le_u16
+            >> name_length: le_u16
+            >> data_type: le_u16
+            >> data_length: le_u32
+            >> name: take!(name_length)
+            >> data: take!(data_length)
+            >> (MetadataLibraryRecord {
+                language_list_index,
+                stream_number,
+                name_length,
+                data_type,
+                data_length,
+                name: Cow::Borrowed(name),
+                data: Cow::Borrowed(data),
+            })
+    )
+);
+
+named!(
+    pub parse_metadata_library_object<MetadataLibraryObject>,
+    do_parse!(
+        object_size: le_u64
+            >> description_records_count: le_u16
+            >> description_records:
+                many_m_n!(
+                    description_records_count as usize,
+                    description_records_count as usize,
+                    parse_metadata_library_record
+                )
+            >> (MetadataLibraryObject {
+                object_id: Cow::Borrowed(&METADATA_LIBRARY_OBJECT_GUID[..]),
+                object_size,
+                description_records_count,
+                description_records,
+            })
+    )
+);
+
+#[test]
+fn attribute_value_test() {
+    assert_eq!(
+        AttributeValue::parse(DATA_TYPE_UNICODE_STRING, &utf16::encode("abc\0")),
+        AttributeValue::String("abc".to_string())
+    );
+    assert_eq!(
+        AttributeValue::parse(DATA_TYPE_BOOL, &[1, 0]),
+        AttributeValue::Bool(true)
+    );
+    assert_eq!(
+        AttributeValue::parse(DATA_TYPE_DWORD, &[0x10, 0x27, 0, 0]),
+        AttributeValue::Dword(10000)
+    );
+    assert_eq!(
+        AttributeValue::parse(DATA_TYPE_WORD, &[1, 2, 3]),
+        AttributeValue::Bytes(vec![1, 2, 3])
+    );
+}
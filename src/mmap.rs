@@ -0,0 +1,129 @@
+use std::fs::File;
+use std::ops::Range;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::media::{MediaObjectAssembler, MediaObjects};
+use crate::packet::DataPackets;
+use crate::raw::parse_raw_object;
+use crate::{
+    parse_asf, parse_guid, parse_simple_index_object, Result, SimpleIndexObject, ASF,
+    SIMPLE_INDEX_OBJECT_GUID,
+};
+
+// メモリマップしたASFファイル
+pub struct MmapAsf {
+    mmap: Mmap,
+    // Data Object の data_packets のマップ上の範囲
+    data_packets: Range<usize>,
+    // Data Object に続くインデックスなどのオブジェクトのマップ上の範囲
+    index_objects: Range<usize>,
+    // File Properties Object のデータパケットのサイズ
+    packet_size: u32,
+    // Stream Properties Object に従って初期化したもの
+    assembler: MediaObjectAssembler,
+    // DRM で保護されているかどうか
+    protected: bool,
+}
+
+// ファイルをメモリマップし、Header Objectを解析する
+pub fn open_mmap<P: AsRef<Path>>(path: P) -> Result<MmapAsf> {
+    let file = File::open(path)?;
+    // Safety: マッピング中にファイルが他のプロセスから変更されないことを前提とする
+    let mmap = unsafe { Mmap::map(&file)? };
+
+    let (data_packets, index_objects, packet_size, assembler, protected) = {
+        let (remain, asf) = parse_asf(&mmap)?;
+        let data_packets = match asf.data_object {
+            Some(ref data_object) => {
+                let start = data_object.data_packets.as_ptr() as usize - mmap.as_ptr() as usize;
+                start..start + data_object.data_packets.len()
+            }
+            None => 0..0,
+        };
+        let index_objects = data_packets.end..mmap.len() - remain.len();
+        let packet_size = match asf.file_props_object {
+            Some(ref file_props_object) => file_props_object.min_data_packet_size,
+            None => 0,
+        };
+        let assembler = MediaObjectAssembler::for_streams(&asf.stream_props_objects);
+        (
+            data_packets,
+            index_objects,
+            packet_size,
+            assembler,
+            asf.is_protected(),
+        )
+    };
+
+    Ok(MmapAsf {
+        mmap,
+        data_packets,
+        index_objects,
+        packet_size,
+        assembler,
+        protected,
+    })
+}
+
+impl MmapAsf {
+    // マップしたファイル全体
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    // マップした領域全体を解析する. データは全てマップした領域を借用する. インデックスだけなら simple_indexes の方が速い
+    pub fn asf(&self) -> Result<ASF<'_>> {
+        let (_, asf) = parse_asf(&self.mmap)?;
+        Ok(asf)
+    }
+
+    // Data Object のパケットを先頭から順に解析する. Data Object が無い場合は空
+    pub fn packets(&self) -> DataPackets<'_> {
+        DataPackets::new(&self.mmap[self.data_packets.clone()], self.packet_size)
+    }
+
+    // Data Object のパケットからメディアオブジェクトを順に組み立てる.
+    // DRM で保護されている場合は最初の要素が Error::Protected になる
+    pub fn media_objects(&self) -> MediaObjects<'_> {
+        MediaObjects::new(self.packets(), self.assembler.clone()).protected(self.protected)
+    }
+
+    // Simple Index Object の一覧. Data Object より後ろだけを解析する
+    pub fn simple_indexes(&self) -> Result<Vec<SimpleIndexObject<'_>>> {
+        let mut input = &self.mmap[self.index_objects.clone()];
+        let mut simple_index_objects = Vec::new();
+        while !input.is_empty() {
+            let (remain, guid) = parse_guid(input)?;
+            if guid == SIMPLE_INDEX_OBJECT_GUID {
+                let (remain, simple_index_object) = parse_simple_index_object(remain)?;
+                simple_index_objects.push(simple_index_object);
+                input = remain;
+            } else {
+                let (remain, _) = parse_raw_object(input, 0)?;
+                input = remain;
+            }
+        }
+        Ok(simple_index_objects)
+    }
+}
+
+#[test]
+fn open_mmap_test1() {
+    let input = include_bytes!("../assets/kte.asf");
+    let asf_file = open_mmap("assets/kte.asf").unwrap();
+
+    let (_, ans) = parse_asf(input).unwrap();
+    assert_eq!(asf_file.asf().unwrap(), ans);
+    assert_eq!(asf_file.simple_indexes().unwrap(), ans.simple_index_objects);
+    assert_eq!(asf_file.index_objects.end, input.len());
+    assert_eq!(
+        asf_file.index_objects.len() as u64,
+        ans.simple_index_objects[0].object_size
+    );
+
+    let packets = asf_file.packets().collect::<Result<Vec<_>>>().unwrap();
+    assert_eq!(packets.len(), 153);
+    assert!(packets.iter().all(|packet| !packet.payloads.is_empty()));
+}
@@ -0,0 +1,737 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::io::{self, Seek, SeekFrom, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::audio::AudioFormat;
+use crate::extension::HeaderExtensionObject;
+use crate::index::{KeyFrame, SimpleIndexBuilder, INDEX_ENTRY_TIME_INTERVAL};
+use crate::packet::{DataPacket, Payload};
+use crate::video::VideoFormat;
+use crate::writer::WriteObject;
+use crate::{
+    BitrateRecord, Error, FilePropertiesObject, HeaderObject, Result, SimpleIndexObject,
+    StreamBitratePropertiesObject, StreamPropertiesObject, DATA_OBJECT_GUID, FILETIME_UNIX_EPOCH,
+    FILE_PROPERTIES_FLAG_BROADCAST, FILE_PROPERTIES_FLAG_SEEKABLE, FILE_PROPERTIES_OBJECT_GUID,
+    HEADER_EXTENSION_OBJECT_GUID, HEADER_EXTENSION_OBJECT_RESERVED_GUID, HEADER_OBJECT_GUID,
+    STREAM_BITRATE_PROPERTIES_OBJECT_GUID,
+    STREAM_PROPERTIES_OBJECT_ERROR_CORRECTION_TYPE_NO_ERROR_CORRECTION_GUID,
+    STREAM_PROPERTIES_OBJECT_GUID, STREAM_PROPERTIES_OBJECT_STREAM_TYPE_AUDIO_GUID,
+    STREAM_PROPERTIES_OBJECT_STREAM_TYPE_VIDEO_GUID,
+};
+
+// Error Correction Present, Error Correction Data Length 2
+const ERROR_CORRECTION_FLAGS: u8 = 0x82;
+// Multiple Payloads Present, Padding Length Type WORD
+const LENGTH_TYPE_FLAGS: u8 = 0x11;
+// Padding Length Type DWORD. 0xFFFF を超えるパケットサイズで使う
+const LENGTH_TYPE_FLAGS_DWORD_PADDING: u8 = 0x19;
+// Replicated Data Length BYTE, Offset Into Media Object DWORD, Media Object Number BYTE, Stream Number BYTE
+const PROPERTY_FLAGS: u8 = 0x5D;
+// Payload Length Type WORD
+const PAYLOAD_FLAGS: u8 = 0x80;
+// Number of Payloads は 6bits
+const MAX_PAYLOADS: usize = 0x3F;
+// Stream Number, Media Object Number, Offset Into Media Object, Replicated Data Length,
+// Replicated Data(Media Object Size, Presentation Time), Payload Length
+const PAYLOAD_HEADER_SIZE: u32 = 1 + 1 + 4 + 1 + 8 + 2;
+// Header Object の GUID, Object Size, Number of Header Objects, Reserved 1, Reserved 2
+const HEADER_OBJECT_HEADER_SIZE: u64 = 30;
+// Data Object の GUID, Object Size, File ID, Total Data Packets, Reserved
+const DATA_OBJECT_HEADER_SIZE: u64 = 50;
+
+// 多重化するストリームの形式
+#[derive(Debug, PartialEq, Eq)]
+pub enum StreamFormat<'a> {
+    // WAVEFORMATEX
+    Audio(AudioFormat<'a>),
+    // BITMAPINFOHEADER
+    Video(VideoFormat<'a>),
+}
+
+impl<'a> StreamFormat<'a> {
+    fn is_video(&self) -> bool {
+        match *self {
+            StreamFormat::Audio(_) => false,
+            StreamFormat::Video(_) => true,
+        }
+    }
+
+    fn stream_type(&self) -> &'static [u8] {
+        match *self {
+            StreamFormat::Audio(_) => &STREAM_PROPERTIES_OBJECT_STREAM_TYPE_AUDIO_GUID,
+            StreamFormat::Video(_) => &STREAM_PROPERTIES_OBJECT_STREAM_TYPE_VIDEO_GUID,
+        }
+    }
+
+    fn type_specific_data(&self) -> Vec<u8> {
+        match *self {
+            StreamFormat::Audio(ref v) => v.to_bytes(),
+            StreamFormat::Video(ref v) => v.to_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MuxerOptions {
+    // データパケットのサイズ
+    pub packet_size: u32,
+    // ファイル再生を始める前に必要なバッファリング時間
+    pub preroll: Duration,
+    // File Properties Object, Data Object, Simple Index Object の File ID
+    pub file_id: [u8; 16],
+    // ファイルの作成日
+    pub creation_time: SystemTime,
+    // ブロードキャスト. File Size などを 0 のまま書き込み、finish の代わりに close でシークせずに終了する
+    pub broadcast: bool,
+    // ストリームの順の平均ビットレート(bits/s). ブロードキャストの場合はフレームを書き込む前にヘッダーを書くので、
+    // Stream Bitrate Properties Object と Maximum Bitrate にはこの値を使う. 無いストリームは 0
+    pub bitrates: Vec<u32>,
+}
+
+impl Default for MuxerOptions {
+    fn default() -> MuxerOptions {
+        MuxerOptions {
+            packet_size: 3200,
+            preroll: Duration::from_millis(3000),
+            file_id: [0; 16],
+            creation_time: SystemTime::now(),
+            broadcast: false,
+            bitrates: Vec::new(),
+        }
+    }
+}
+
+// 多重化中のストリームの状態
+struct MuxStream {
+    stream_number: u8,
+    is_video: bool,
+    // 次のメディアオブジェクトの番号
+    media_object_number: u32,
+    // メディアオブジェクトの総バイト数
+    total_bytes: u64,
+    // 最初と最後のメディアオブジェクトの表示時間(ミリ秒)と、最後のメディアオブジェクトの長さ
+    first_time: Option<u32>,
+    last_time: u32,
+    last_duration: u32,
+    index: SimpleIndexBuilder,
+}
+
+impl MuxStream {
+    // 最後のメディアオブジェクトの終了時間(ミリ秒). プリロールを含む
+    fn end_time(&self) -> u32 {
+        self.last_time.saturating_add(self.last_duration)
+    }
+
+    // 平均ビットレート(bits/s)
+    fn average_bitrate(&self) -> u32 {
+        let duration = u64::from(self.end_time() - self.first_time.unwrap_or(0));
+        if duration == 0 {
+            return 0;
+        }
+        (self.total_bytes * 8 * 1000 / duration).min(u64::from(u32::MAX)) as u32
+    }
+}
+
+// ストリームのフレームを固定長のデータパケットにまとめ、ASF ファイルとして書き込む
+pub struct Muxer<W> {
+    writer: W,
+    options: MuxerOptions,
+    streams: Vec<MuxStream>,
+    stream_props_objects: Vec<StreamPropertiesObject<'static>>,
+    // 書き込み中のパケットのペイロードと、そのサイズの合計
+    payloads: Vec<Payload<'static>>,
+    payloads_size: u32,
+    // 書き込み中のパケットの送信時間(ミリ秒)
+    send_time: u32,
+    // 書き込んだパケットの数
+    data_packets_count: u64,
+    // 書き込んだバイト数
+    written: u64,
+}
+
+impl<W: Write> Muxer<W> {
+    // streams の順にストリーム番号 1, 2, ... を割り当て、Header Object と Data Object の先頭を書き込む
+    pub fn new(writer: W, streams: Vec<StreamFormat>, options: MuxerOptions) -> Result<Muxer<W>> {
+        if streams.len() > 127 {
+            return Err(Error::InvalidStreamNumber(128));
+        }
+        if u32::try_from(options.preroll.as_millis()).is_err() {
+            return Err(Error::InvalidTimestamp(options.preroll));
+        }
+        let mut muxer = Muxer {
+            writer,
+            options,
+            streams: Vec::new(),
+            stream_props_objects: Vec::new(),
+            payloads: Vec::new(),
+            payloads_size: 0,
+            send_time: 0,
+            data_packets_count: 0,
+            written: 0,
+        };
+        if muxer.payload_capacity() == 0 {
+            return Err(Error::InvalidPacketSize(muxer.options.packet_size));
+        }
+        for (i, format) in streams.iter().enumerate() {
+            let stream_number = i as u8 + 1;
+            let type_specific_data = format.type_specific_data();
+            muxer.stream_props_objects.push(StreamPropertiesObject {
+                object_id: Cow::Borrowed(&STREAM_PROPERTIES_OBJECT_GUID),
+                object_size: 78 + type_specific_data.len() as u64,
+                stream_type: Cow::Borrowed(format.stream_type()),
+                error_correction_type: Cow::Borrowed(
+                    &STREAM_PROPERTIES_OBJECT_ERROR_CORRECTION_TYPE_NO_ERROR_CORRECTION_GUID,
+                ),
+                time_offset: 0,
+                type_specific_data_length: type_specific_data.len() as u32,
+                error_correction_data_length: 0,
+                flags: u16::from(stream_number),
+                reserved: 0,
+                type_specific_data: Cow::Owned(type_specific_data),
+                error_correction_data: Cow::Borrowed(&[]),
+            });
+            muxer.streams.push(MuxStream {
+                stream_number,
+                is_video: format.is_video(),
+                media_object_number: 0,
+                total_bytes: 0,
+                first_time: None,
+                last_time: 0,
+                last_duration: 0,
+                index: SimpleIndexBuilder::new(),
+            });
+        }
+
+        // File Size などは finish で書き直す
+        let header = muxer.header(0)?;
+        muxer.writer.write_all(&header)?;
+        muxer.written += header.len() as u64;
+        Ok(muxer)
+    }
+
+    fn length_type_flags(&self) -> u8 {
+        if self.options.packet_size > 0xFFFF {
+            LENGTH_TYPE_FLAGS_DWORD_PADDING
+        } else {
+            LENGTH_TYPE_FLAGS
+        }
+    }
+
+    // パケットヘッダーのサイズ
+    fn packet_header_size(&self) -> u32 {
+        let padding_length_size = if self.options.packet_size > 0xFFFF {
+            4
+        } else {
+            2
+        };
+        // Error Correction Data, Length Type Flags, Property Flags, Padding Length,
+        // Send Time, Duration, Payload Flags
+        3 + 2 + padding_length_size + 6 + 1
+    }
+
+    // 書き込み中のパケットに追加できるペイロードデータのサイズ
+    fn payload_capacity(&self) -> u32 {
+        self.options
+            .packet_size
+            .saturating_sub(self.packet_header_size() + self.payloads_size + PAYLOAD_HEADER_SIZE)
+            .min(u32::from(u16::MAX))
+    }
+
+    // new で u32 に収まることを確かめている
+    fn preroll(&self) -> u32 {
+        u32::try_from(self.options.preroll.as_millis()).unwrap_or(u32::MAX)
+    }
+
+    // ストリームの平均ビットレート(bits/s). ブロードキャストの場合は MuxerOptions で指定した値
+    fn bitrate(&self, index: usize) -> u32 {
+        if self.options.broadcast {
+            self.options.bitrates.get(index).copied().unwrap_or(0)
+        } else {
+            self.streams[index].average_bitrate()
+        }
+    }
+
+    // 再生時間(100ナノ秒単位). プリロールを含む
+    fn play_duration(&self) -> u64 {
+        self.streams
+            .iter()
+            .filter(|v| v.first_time.is_some())
+            .map(|v| u64::from(v.end_time()) * 10_000)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn file_props_object(&self, file_size: u64) -> FilePropertiesObject<'_> {
+        let creation_date = match self.options.creation_time.duration_since(UNIX_EPOCH) {
+            Ok(v) => FILETIME_UNIX_EPOCH + v.as_nanos() as u64 / 100,
+            Err(e) => FILETIME_UNIX_EPOCH.saturating_sub(e.duration().as_nanos() as u64 / 100),
+        };
+        FilePropertiesObject {
+            object_id: Cow::Borrowed(&FILE_PROPERTIES_OBJECT_GUID),
+            object_size: 104,
+            file_id: Cow::Borrowed(&self.options.file_id),
+            file_size,
+            creation_date,
+            data_packets_count: self.data_packets_count,
+            play_duration: self.play_duration(),
+            send_duration: u64::from(self.send_time) * 10_000,
+            preoll: u64::from(self.preroll()),
+            flags: if self.options.broadcast {
+                FILE_PROPERTIES_FLAG_BROADCAST
+            } else {
+                FILE_PROPERTIES_FLAG_SEEKABLE
+            },
+            min_data_packet_size: self.options.packet_size,
+            max_data_packet_size: self.options.packet_size,
+            max_bitrate: (0..self.streams.len())
+                .fold(0u32, |sum, i| sum.saturating_add(self.bitrate(i))),
+        }
+    }
+
+    // Header Object と Data Object の先頭. ストリームの構成が同じならサイズは変わらない
+    fn header(&self, file_size: u64) -> Result<Vec<u8>> {
+        let header_extension_object = HeaderExtensionObject {
+            object_id: Cow::Borrowed(&HEADER_EXTENSION_OBJECT_GUID),
+            object_size: 46,
+            reserved_field_1: Cow::Borrowed(&HEADER_EXTENSION_OBJECT_RESERVED_GUID),
+            reserved_field_2: 6,
+            header_extension_data_size: 0,
+            advanced_mutual_exclusion_objects: Vec::new(),
+            group_mutual_exclusion_objects: Vec::new(),
+            stream_prioritization_object: None,
+            bandwidth_sharing_objects: Vec::new(),
+            language_list_object: None,
+            extended_stream_props_objects: Vec::new(),
+            metadata_library_object: None,
+            compatibility_object: None,
+            unknown_objects: Vec::new(),
+            object_order: Vec::new(),
+        };
+        let bitrate_records: Vec<BitrateRecord> = self
+            .streams
+            .iter()
+            .enumerate()
+            .map(|(i, v)| BitrateRecord {
+                flags: u16::from(v.stream_number),
+                average_bitrate: self.bitrate(i),
+            })
+            .collect();
+        let stream_bitrate_props_object = StreamBitratePropertiesObject {
+            object_id: Cow::Borrowed(&STREAM_BITRATE_PROPERTIES_OBJECT_GUID),
+            object_size: 26 + 6 * bitrate_records.len() as u64,
+            bitrate_records_count: bitrate_records.len() as u16,
+            bitrate_records,
+        };
+
+        let mut header_objects = Vec::new();
+        self.file_props_object(file_size)
+            .write_to(&mut header_objects)?;
+        for v in &self.stream_props_objects {
+            v.write_to(&mut header_objects)?;
+        }
+        header_extension_object.write_to(&mut header_objects)?;
+        stream_bitrate_props_object.write_to(&mut header_objects)?;
+
+        let header_object = HeaderObject {
+            object_id: Cow::Borrowed(&HEADER_OBJECT_GUID),
+            object_size: HEADER_OBJECT_HEADER_SIZE + header_objects.len() as u64,
+            num_header_objects: 3 + self.stream_props_objects.len() as u32,
+            reserved_1: 1,
+            reserved_2: 2,
+        };
+        let mut bytes = header_object.to_bytes();
+        bytes.extend_from_slice(&header_objects);
+        // ブロードキャストの場合、Data Object のサイズは不明なので 0 にする
+        let data_object_size = if self.options.broadcast {
+            0
+        } else {
+            DATA_OBJECT_HEADER_SIZE + self.data_packets_count * u64::from(self.options.packet_size)
+        };
+        bytes.extend_from_slice(&DATA_OBJECT_GUID);
+        bytes.extend_from_slice(&data_object_size.to_le_bytes());
+        bytes.extend_from_slice(&self.options.file_id);
+        bytes.extend_from_slice(&self.data_packets_count.to_le_bytes());
+        bytes.extend_from_slice(&0x0101u16.to_le_bytes());
+        Ok(bytes)
+    }
+
+    // 書き込み中のパケットを書き込む
+    fn flush_packet(&mut self) -> Result<()> {
+        if self.payloads.is_empty() {
+            return Ok(());
+        }
+        let packet = DataPacket {
+            error_correction_flags: Some(ERROR_CORRECTION_FLAGS),
+            error_correction_data: Cow::Borrowed(&[0, 0]),
+            length_type_flags: self.length_type_flags(),
+            property_flags: PROPERTY_FLAGS,
+            packet_length: self.options.packet_size,
+            sequence: 0,
+            padding_length: 0,
+            send_time: self.send_time,
+            duration: 0,
+            payload_flags: Some(PAYLOAD_FLAGS | self.payloads.len() as u8),
+            payloads: self.payloads.split_off(0),
+        };
+        packet.write_to(&mut self.writer, self.options.packet_size)?;
+        self.payloads_size = 0;
+        self.data_packets_count += 1;
+        self.written += u64::from(self.options.packet_size);
+        if self.options.broadcast {
+            // パイプやソケットの受信側がすぐに読めるようにする
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    // フレームを1つのメディアオブジェクトとして追加する. パケットに収まらない場合は分割する
+    pub fn write_frame(
+        &mut self,
+        stream_number: u8,
+        presentation_time: Duration,
+        key_frame: bool,
+        data: &[u8],
+    ) -> Result<()> {
+        let index = self
+            .streams
+            .iter()
+            .position(|v| v.stream_number == stream_number)
+            .ok_or(Error::InvalidStreamNumber(stream_number))?;
+        // Replicated Data の表示時間はプリロールを含む
+        let (time, presentation_time) = match u32::try_from(presentation_time.as_millis()) {
+            Ok(time) => match time.checked_add(self.preroll()) {
+                Some(v) => (time, v),
+                None => return Err(Error::InvalidTimestamp(presentation_time)),
+            },
+            Err(_) => return Err(Error::InvalidTimestamp(presentation_time)),
+        };
+        let media_object_number = self.streams[index].media_object_number;
+        let mut replicated_data = Vec::with_capacity(8);
+        replicated_data.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        replicated_data.extend_from_slice(&presentation_time.to_le_bytes());
+
+        let mut offset = 0;
+        let mut first_packet = None;
+        loop {
+            if self.payloads.len() == MAX_PAYLOADS || self.payload_capacity() == 0 {
+                self.flush_packet()?;
+            }
+            if self.payloads.is_empty() {
+                // 送信時間は単調増加にする
+                self.send_time = self.send_time.max(time);
+            }
+            first_packet.get_or_insert(self.data_packets_count as u32);
+            let len = (data.len() - offset).min(self.payload_capacity() as usize);
+            self.payloads.push(Payload {
+                stream_number,
+                key_frame,
+                // Media Object Number Length Type は BYTE
+                media_object_number: media_object_number & 0xFF,
+                offset_into_media_object: offset as u32,
+                replicated_data_length: 8,
+                replicated_data: Cow::Owned(replicated_data.clone()),
+                payload_length: len as u32,
+                payload_data: Cow::Owned(data[offset..offset + len].to_vec()),
+            });
+            self.payloads_size += PAYLOAD_HEADER_SIZE + len as u32;
+            offset += len;
+            if offset == data.len() {
+                break;
+            }
+        }
+
+        let last_packet = self.data_packets_count as u32;
+        let stream = &mut self.streams[index];
+        stream.media_object_number = media_object_number.wrapping_add(1);
+        stream.total_bytes += data.len() as u64;
+        if stream.first_time.is_some() {
+            stream.last_duration = presentation_time.saturating_sub(stream.last_time);
+        }
+        stream.first_time.get_or_insert(presentation_time);
+        stream.last_time = stream.last_time.max(presentation_time);
+        if key_frame {
+            stream.index.push(KeyFrame {
+                presentation_time: u64::from(presentation_time) * 10_000,
+                first_packet: first_packet.unwrap_or(last_packet),
+                last_packet,
+            });
+        }
+        Ok(())
+    }
+
+    // 映像ストリームごとの Simple Index Object
+    fn simple_index_objects(&self) -> Vec<SimpleIndexObject<'static>> {
+        let play_duration = self.play_duration();
+        self.streams
+            .iter()
+            .filter(|v| v.is_video)
+            .map(|v| {
+                v.index.build(
+                    &self.options.file_id,
+                    INDEX_ENTRY_TIME_INTERVAL,
+                    play_duration,
+                )
+            })
+            .collect()
+    }
+
+    // 残りのパケットを書き込んで終了する. シークしないので、ブロードキャストの場合に使う.
+    // ブロードキャストでない場合、ヘッダーのサイズや総数を書き直せないのでエラーにする
+    pub fn close(mut self) -> Result<W> {
+        if !self.options.broadcast {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "close requires broadcast mode, use finish instead",
+            )
+            .into());
+        }
+        self.flush_packet()?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+impl<W: Write + Seek> Muxer<W> {
+    // 残りのパケットと Simple Index Object を書き込み、File Properties Object と Data Object を書き直す
+    // ブロードキャストの場合は close と同じ
+    pub fn finish(mut self) -> Result<W> {
+        if self.options.broadcast {
+            return self.close();
+        }
+        self.flush_packet()?;
+        let mut index = Vec::new();
+        for v in self.simple_index_objects() {
+            v.write_to(&mut index)?;
+        }
+        self.writer.write_all(&index)?;
+        self.written += index.len() as u64;
+
+        let header = self.header(self.written)?;
+        self.writer
+            .seek(SeekFrom::Current(-(self.written as i64)))?;
+        self.writer.write_all(&header)?;
+        self.writer.seek(SeekFrom::End(0))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[test]
+fn muxer_test() {
+    use std::io::Cursor;
+
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let audio_format = asf.stream_props_objects[0].audio_format().unwrap();
+    let video_format = asf.stream_props_objects[1].video_format().unwrap();
+
+    let options = MuxerOptions {
+        packet_size: 1000,
+        preroll: Duration::from_millis(2000),
+        file_id: [7; 16],
+        creation_time: UNIX_EPOCH,
+        broadcast: false,
+        bitrates: Vec::new(),
+    };
+    let mut muxer = Muxer::new(
+        Cursor::new(Vec::new()),
+        vec![
+            StreamFormat::Audio(audio_format),
+            StreamFormat::Video(video_format),
+        ],
+        options,
+    )
+    .unwrap();
+    let mut frames = Vec::new();
+    for i in 0..30u32 {
+        let time = i * 100;
+        frames.push((1, time, true, vec![i as u8; 300]));
+        // 2秒ごとにキーフレーム. キーフレームは複数のパケットに分割される
+        let key_frame = i % 20 == 0;
+        let size = if key_frame { 2500 } else { 200 };
+        frames.push((2, time, key_frame, vec![i as u8 + 100; size]));
+    }
+    for (stream_number, time, key_frame, data) in &frames {
+        muxer
+            .write_frame(
+                *stream_number,
+                Duration::from_millis(u64::from(*time)),
+                *key_frame,
+                data,
+            )
+            .unwrap();
+    }
+    match muxer.write_frame(3, Duration::from_millis(0), false, &[]) {
+        Err(Error::InvalidStreamNumber(3)) => {}
+        v => panic!("{:?}", v.err()),
+    }
+    // プリロールを足すと u32 のミリ秒で表せない
+    let time = Duration::from_millis(u64::from(u32::MAX) - 1000);
+    match muxer.write_frame(1, time, false, &[]) {
+        Err(Error::InvalidTimestamp(v)) if v == time => {}
+        v => panic!("{:?}", v.err()),
+    }
+    let output = muxer.finish().unwrap().into_inner();
+
+    let (remain, asf) = crate::parse_asf(&output).unwrap();
+    assert!(remain.is_empty());
+    let file_props_object = asf.file_props_object.as_ref().unwrap();
+    let data_object = asf.data_object.as_ref().unwrap();
+    assert_eq!(file_props_object.file_size, output.len() as u64);
+    assert_eq!(
+        file_props_object.data_packets_count,
+        data_object.total_data_packets
+    );
+    assert_eq!(
+        data_object.data_packets.len() as u64,
+        data_object.total_data_packets * 1000
+    );
+    assert_eq!(&file_props_object.file_id[..], &[7; 16]);
+    assert_eq!(file_props_object.creation_time(), UNIX_EPOCH);
+    assert_eq!(
+        file_props_object.preroll_time(),
+        Duration::from_millis(2000)
+    );
+    assert_eq!(
+        file_props_object.playback_duration(),
+        Some(Duration::from_millis(3000))
+    );
+    assert!(file_props_object.is_seekable());
+    assert_eq!(asf.stream_props_objects[0].stream_number(), 1);
+    assert_eq!(asf.stream_props_objects[1].stream_number(), 2);
+    assert_eq!(
+        asf.stream_props_objects[1].video_format().unwrap().fourcc(),
+        "WMV3"
+    );
+    // 音声は 300bytes を 100ms ごと
+    assert_eq!(asf.bitrate_record(1).unwrap().average_bitrate, 24000);
+
+    let media_objects: Vec<_> = asf.media_objects().unwrap().map(|v| v.unwrap()).collect();
+    assert_eq!(media_objects.len(), frames.len());
+    for (media_object, (stream_number, time, key_frame, data)) in
+        media_objects.iter().zip(frames.iter())
+    {
+        assert_eq!(media_object.stream_number, *stream_number);
+        assert_eq!(media_object.presentation_time, time + 2000);
+        assert_eq!(media_object.key_frame, *key_frame);
+        assert_eq!(&media_object.data, data);
+    }
+
+    // 再生時間 5 秒に対して 6 エントリー. 2 秒のキーフレームは 4 秒の位置から参照される
+    assert_eq!(asf.simple_index_objects.len(), 1);
+    let simple_index_object = &asf.simple_index_objects[0];
+    assert_eq!(simple_index_object.index_entries.len(), 6);
+    let first = &simple_index_object.index_entries[0];
+    assert_eq!(first.packet_number, 0);
+    assert_eq!(first.packet_count, 3);
+    assert_eq!(simple_index_object.index_entries[1], *first);
+    assert_eq!(simple_index_object.index_entries[3], *first);
+    let second = &simple_index_object.index_entries[4];
+    assert!(second.packet_number > 0);
+    assert_eq!(second.packet_count, 4);
+    assert_eq!(simple_index_object.index_entries[5], *second);
+
+    let mut muxer = Muxer::new(
+        Vec::new(),
+        Vec::new(),
+        MuxerOptions {
+            packet_size: 30,
+            ..MuxerOptions::default()
+        },
+    );
+    match muxer {
+        Err(Error::InvalidPacketSize(30)) => {}
+        _ => panic!(),
+    }
+    muxer = Muxer::new(
+        Vec::new(),
+        Vec::new(),
+        MuxerOptions {
+            preroll: Duration::from_millis(u64::from(u32::MAX) + 1),
+            ..MuxerOptions::default()
+        },
+    );
+    match muxer {
+        Err(Error::InvalidTimestamp(_)) => {}
+        _ => panic!(),
+    }
+    muxer = Muxer::new(Vec::new(), Vec::new(), MuxerOptions::default());
+    assert!(muxer.is_ok());
+}
+
+#[test]
+fn broadcast_muxer_test() {
+    let input = include_bytes!("../assets/320x180_10fps.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let video_format = asf.stream_props_objects[0].video_format().unwrap();
+
+    // Vec<u8> は Seek を実装していない
+    let mut muxer = Muxer::new(
+        Vec::new(),
+        vec![StreamFormat::Video(video_format)],
+        MuxerOptions {
+            packet_size: 500,
+            broadcast: true,
+            bitrates: vec![32000],
+            ..MuxerOptions::default()
+        },
+    )
+    .unwrap();
+    for i in 0..20u64 {
+        muxer
+            .write_frame(
+                1,
+                Duration::from_millis(i * 100),
+                i % 10 == 0,
+                &[i as u8; 400],
+            )
+            .unwrap();
+    }
+    let output = muxer.close().unwrap();
+
+    let (remain, asf) = crate::parse_asf(&output).unwrap();
+    assert!(remain.is_empty());
+    let file_props_object = asf.file_props_object.as_ref().unwrap();
+    assert!(file_props_object.is_broadcast());
+    assert!(!file_props_object.is_seekable());
+    assert_eq!(file_props_object.file_size, 0);
+    assert_eq!(file_props_object.data_packets_count, 0);
+    assert_eq!(file_props_object.known_file_size(), None);
+    // ビットレートはヘッダーを書く時点では分からないので、指定した値を使う
+    assert_eq!(file_props_object.max_bitrate, 32000);
+    assert_eq!(asf.bitrate_record(1).unwrap().average_bitrate, 32000);
+    let data_object = asf.data_object.as_ref().unwrap();
+    assert_eq!(data_object.object_size, 0);
+    assert_eq!(data_object.total_data_packets, 0);
+    assert_eq!(data_object.data_packets.len() % 500, 0);
+    assert!(asf.simple_index_objects.is_empty());
+
+    let media_objects: Vec<_> = asf.media_objects().unwrap().map(|v| v.unwrap()).collect();
+    assert_eq!(media_objects.len(), 20);
+    for (i, media_object) in media_objects.iter().enumerate() {
+        assert_eq!(media_object.presentation_time, i as u32 * 100 + 3000);
+        assert_eq!(media_object.key_frame, i % 10 == 0);
+        assert_eq!(media_object.data, vec![i as u8; 400]);
+    }
+
+    // ブロードキャストでない場合はシークして書き直す必要がある
+    let mut muxer = Muxer::new(
+        Vec::new(),
+        vec![StreamFormat::Video(
+            asf.stream_props_objects[0].video_format().unwrap(),
+        )],
+        MuxerOptions {
+            packet_size: 500,
+            ..MuxerOptions::default()
+        },
+    )
+    .unwrap();
+    muxer
+        .write_frame(1, Duration::from_millis(0), true, &[0; 400])
+        .unwrap();
+    match muxer.close() {
+        Err(Error::Io(ref e)) if e.kind() == io::ErrorKind::InvalidInput => {}
+        v => panic!("{:?}", v.err()),
+    }
+}
@@ -0,0 +1,343 @@
+use std::borrow::Cow;
+
+use nom::{le_u16, le_u32, le_u8, ErrorKind, IResult};
+
+// Error Correction Flags の Error Correction Present
+const ERROR_CORRECTION_PRESENT: u8 = 0x80;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Payload<'a> {
+    // ストリーム番号(7bits)
+    pub stream_number: u8,
+    // キーフレームかどうか(1bit)
+    pub key_frame: bool,
+    // このペイロードが属するメディアオブジェクトの番号
+    pub media_object_number: u32,
+    // メディアオブジェクト内のオフセット. 圧縮ペイロードの場合は表示時間
+    pub offset_into_media_object: u32,
+    // Replicated Dataのサイズ. 1の場合は圧縮ペイロード
+    pub replicated_data_length: u32,
+    // メディアオブジェクトのサイズ(4bytes)と表示時間(4bytes)などが含まれる
+    pub replicated_data: Cow<'a, [u8]>,
+    // Payload Dataのサイズ
+    pub payload_length: u32,
+    pub payload_data: Cow<'a, [u8]>,
+}
+
+impl<'a> Payload<'a> {
+    // 複数のサブペイロードがまとめられた圧縮ペイロードかどうか
+    pub fn is_compressed(&self) -> bool {
+        self.replicated_data_length == 1
+    }
+
+    // メディアオブジェクト全体のサイズ
+    pub fn media_object_size(&self) -> Option<u32> {
+        if self.replicated_data.len() < 8 {
+            return None;
+        }
+        Some(read_u32(&self.replicated_data[0..4]))
+    }
+
+    // メディアオブジェクトの表示時間(ミリ秒)
+    pub fn presentation_time(&self) -> Option<u32> {
+        if self.is_compressed() {
+            return Some(self.offset_into_media_object);
+        }
+        if self.replicated_data.len() < 8 {
+            return None;
+        }
+        Some(read_u32(&self.replicated_data[4..8]))
+    }
+
+    // メディアオブジェクトの表示時間(ミリ秒)を書き換える. Replicated Data が無い場合は何もしない
+    pub fn set_presentation_time(&mut self, presentation_time: u32) {
+        if self.is_compressed() {
+            self.offset_into_media_object = presentation_time;
+        } else if self.replicated_data.len() >= 8 {
+            self.replicated_data.to_mut()[4..8].copy_from_slice(&presentation_time.to_le_bytes());
+        }
+    }
+
+    // 圧縮ペイロードの場合、サブペイロードを順に返す
+    pub fn sub_payloads(&self) -> SubPayloads<'_> {
+        let data: &[u8] = if self.is_compressed() {
+            &self.payload_data
+        } else {
+            &[]
+        };
+        SubPayloads { data }
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> Payload<'static> {
+        Payload {
+            stream_number: self.stream_number,
+            key_frame: self.key_frame,
+            media_object_number: self.media_object_number,
+            offset_into_media_object: self.offset_into_media_object,
+            replicated_data_length: self.replicated_data_length,
+            replicated_data: Cow::Owned(self.replicated_data.into_owned()),
+            payload_length: self.payload_length,
+            payload_data: Cow::Owned(self.payload_data.into_owned()),
+        }
+    }
+}
+
+pub struct SubPayloads<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for SubPayloads<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let (&len, rest) = self.data.split_first()?;
+        let len = (len as usize).min(rest.len());
+        let (sub_payload, rest) = rest.split_at(len);
+        self.data = rest;
+        Some(sub_payload)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DataPacket<'a> {
+    // Error Correction Data Length(4bits), Opaque Data Present(1bit), Error Correction Length Type(2bits), Error Correction Present(1bit)
+    pub error_correction_flags: Option<u8>,
+    pub error_correction_data: Cow<'a, [u8]>,
+    // Multiple Payloads Present(1bit), Sequence Type(2bits), Padding Length Type(2bits), Packet Length Type(2bits), Error Correction Present(1bit)
+    pub length_type_flags: u8,
+    // Replicated Data Length Type(2bits), Offset Into Media Object Length Type(2bits), Media Object Number Length Type(2bits), Stream Number Length Type(2bits)
+    pub property_flags: u8,
+    // パケットのサイズ. 0の場合はFile Properties Objectのパケットサイズ
+    pub packet_length: u32,
+    // 予約領域
+    pub sequence: u32,
+    // パケット末尾のパディングのサイズ
+    pub padding_length: u32,
+    // 送信時間(ミリ秒)
+    pub send_time: u32,
+    // パケットの再生時間(ミリ秒)
+    pub duration: u16,
+    // Number of Payloads(6bits), Payload Length Type(2bits). 単一ペイロードの場合は None
+    pub payload_flags: Option<u8>,
+    pub payloads: Vec<Payload<'a>>,
+}
+
+impl<'a> DataPacket<'a> {
+    // Error Correction Data の Type. Uncorrected(0), XOR Data(1), Parity Data(2)
+    pub fn error_correction_type(&self) -> Option<u8> {
+        self.error_correction_data.first().map(|v| v & 0x0F)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> DataPacket<'static> {
+        DataPacket {
+            error_correction_flags: self.error_correction_flags,
+            error_correction_data: Cow::Owned(self.error_correction_data.into_owned()),
+            length_type_flags: self.length_type_flags,
+            property_flags: self.property_flags,
+            packet_length: self.packet_length,
+            sequence: self.sequence,
+            padding_length: self.padding_length,
+            send_time: self.send_time,
+            duration: self.duration,
+            payload_flags: self.payload_flags,
+            payloads: self.payloads.into_iter().map(Payload::into_owned).collect(),
+        }
+    }
+}
+
+fn read_u32(input: &[u8]) -> u32 {
+    u32::from(input[0])
+        | u32::from(input[1]) << 8
+        | u32::from(input[2]) << 16
+        | u32::from(input[3]) << 24
+}
+
+// Length Typeに従って 0, 1, 2, 4 bytes の値を読み込む
+fn parse_var_len(input: &[u8], length_type: u8) -> IResult<&[u8], u32> {
+    match length_type & 0x03 {
+        0 => Ok((input, 0)),
+        1 => map!(input, le_u8, u32::from),
+        2 => map!(input, le_u16, u32::from),
+        _ => le_u32(input),
+    }
+}
+
+fn parse_payload(
+    input: &[u8],
+    property_flags: u8,
+    payload_length_type: Option<u8>,
+    padding: usize,
+) -> IResult<&[u8], Payload<'_>> {
+    let (input, stream_number) = try_parse!(input, le_u8);
+    let (input, media_object_number) = try_parse!(input, call!(parse_var_len, property_flags >> 4));
+    let (input, offset_into_media_object) =
+        try_parse!(input, call!(parse_var_len, property_flags >> 2));
+    let (input, replicated_data_length) = try_parse!(input, call!(parse_var_len, property_flags));
+    let (input, replicated_data) = try_parse!(input, take!(replicated_data_length));
+    let (input, payload_length) = match payload_length_type {
+        Some(length_type) => try_parse!(input, call!(parse_var_len, length_type)),
+        // 単一ペイロードの場合はパディングを除くパケットの残り全て
+        None if input.len() >= padding => (input, (input.len() - padding) as u32),
+        None => return Err(nom::Err::Error(error_position!(input, ErrorKind::Verify))),
+    };
+    let (input, payload_data) = try_parse!(input, take!(payload_length));
+
+    Ok((
+        input,
+        Payload {
+            stream_number: stream_number & 0x7F,
+            key_frame: stream_number & 0x80 != 0,
+            media_object_number,
+            offset_into_media_object,
+            replicated_data_length,
+            replicated_data: Cow::Borrowed(replicated_data),
+            payload_length,
+            payload_data: Cow::Borrowed(payload_data),
+        },
+    ))
+}
+
+// packet_size はFile Properties Objectのデータパケットのサイズ
+pub fn parse_data_packet(input: &[u8], packet_size: u32) -> IResult<&[u8], DataPacket<'_>> {
+    if input.len() < packet_size as usize {
+        let needed = packet_size as usize - input.len();
+        return Err(nom::Err::Incomplete(nom::Needed::Size(needed)));
+    }
+    let (packet, remain) = input.split_at(packet_size as usize);
+
+    let (mut input, flags) = try_parse!(packet, le_u8);
+    let mut error_correction_flags = None;
+    let mut error_correction_data: &[u8] = &[];
+    let mut length_type_flags = flags;
+    if flags & ERROR_CORRECTION_PRESENT != 0 {
+        // Error Correction Length Type は 00 以外定義されていない
+        if flags & 0x60 != 0 {
+            return Err(nom::Err::Error(error_position!(packet, ErrorKind::Verify)));
+        }
+        let (remain, data) = try_parse!(input, take!(flags & 0x0F));
+        let (remain, flags) = try_parse!(remain, le_u8);
+        error_correction_flags = Some(length_type_flags);
+        error_correction_data = data;
+        length_type_flags = flags;
+        input = remain;
+    }
+
+    let (input, property_flags) = try_parse!(input, le_u8);
+    let (input, packet_length) = try_parse!(input, call!(parse_var_len, length_type_flags >> 5));
+    let (input, sequence) = try_parse!(input, call!(parse_var_len, length_type_flags >> 1));
+    let (input, padding_length) = try_parse!(input, call!(parse_var_len, length_type_flags >> 3));
+    let (input, send_time) = try_parse!(input, le_u32);
+    let (mut input, duration) = try_parse!(input, le_u16);
+
+    // パケット末尾のパディングのサイズ. Packet Length が固定長より短い場合はその差もパディングになる
+    let packet_end = if packet_length == 0 {
+        packet_size
+    } else {
+        packet_length.min(packet_size)
+    };
+    let padding = match (packet_size - packet_end).checked_add(padding_length) {
+        Some(padding) if padding as usize <= input.len() => padding as usize,
+        _ => return Err(nom::Err::Error(error_position!(input, ErrorKind::Verify))),
+    };
+
+    let mut payload_flags = None;
+    let mut payloads = Vec::new();
+    if length_type_flags & 0x01 != 0 {
+        let (remain, flags) = try_parse!(input, le_u8);
+        input = remain;
+        for _ in 0..(flags & 0x3F) {
+            let (remain, payload) = try_parse!(
+                input,
+                call!(parse_payload, property_flags, Some(flags >> 6), padding)
+            );
+            payloads.push(payload);
+            input = remain;
+        }
+        payload_flags = Some(flags);
+    } else {
+        let (remain, payload) =
+            try_parse!(input, call!(parse_payload, property_flags, None, padding));
+        payloads.push(payload);
+        input = remain;
+    }
+    if input.len() < padding {
+        return Err(nom::Err::Error(error_position!(input, ErrorKind::Verify)));
+    }
+
+    Ok((
+        remain,
+        DataPacket {
+            error_correction_flags,
+            error_correction_data: Cow::Borrowed(error_correction_data),
+            length_type_flags,
+            property_flags,
+            packet_length,
+            sequence,
+            padding_length,
+            send_time,
+            duration,
+            payload_flags,
+            payloads,
+        },
+    ))
+}
+
+// Data Objectの data_packets を固定長のパケットごとに解析する
+pub struct DataPackets<'a> {
+    input: &'a [u8],
+    packet_size: u32,
+}
+
+impl<'a> DataPackets<'a> {
+    pub fn new(input: &'a [u8], packet_size: u32) -> DataPackets<'a> {
+        DataPackets { input, packet_size }
+    }
+}
+
+impl<'a> Iterator for DataPackets<'a> {
+    type Item = Result<DataPacket<'a>, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() || self.packet_size == 0 {
+            return None;
+        }
+        match parse_data_packet(self.input, self.packet_size) {
+            Ok((remain, packet)) => {
+                self.input = remain;
+                Some(Ok(packet))
+            }
+            Err(e) => {
+                self.input = &[];
+                Some(Err(e.into()))
+            }
+        }
+    }
+}
+
+#[test]
+fn parse_data_packet_test1() {
+    let input = include_bytes!("../assets/320x180_10fps.asf");
+    let (remain, packet) = parse_data_packet(&input[1156..], 3200).unwrap();
+
+    assert_eq!(remain.len(), input.len() - 1156 - 3200);
+    assert_eq!(packet.error_correction_flags, Some(0x82));
+    assert_eq!(packet.error_correction_data, Cow::Borrowed(&[0u8, 0][..]));
+    assert_eq!(packet.length_type_flags, 0x09);
+    assert_eq!(packet.property_flags, 0x5D);
+    assert_eq!(packet.padding_length, 150);
+    assert_eq!(packet.send_time, 0);
+    assert_eq!(packet.duration, 92);
+    assert_eq!(packet.payload_flags, Some(0x84));
+    assert_eq!(packet.payloads.len(), 4);
+
+    let payload = &packet.payloads[0];
+    assert_eq!(payload.stream_number, 2);
+    assert!(!payload.key_frame);
+    assert_eq!(payload.media_object_number, 1);
+    assert_eq!(payload.offset_into_media_object, 0);
+    assert_eq!(payload.media_object_size(), Some(185));
+    assert_eq!(payload.presentation_time(), Some(3100));
+    assert_eq!(payload.payload_length, 185);
+}
@@ -0,0 +1,166 @@
+use std::borrow::Cow;
+
+use nom::{le_u16, le_u32, le_u64};
+
+use crate::{BANDWIDTH_SHARING_OBJECT_GUID, STREAM_PRIORITIZATION_OBJECT_GUID};
+
+// Priority Flags の Mandatory
+pub const PRIORITY_FLAG_MANDATORY: u16 = 0x0001;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PriorityRecord {
+    // ストリーム番号
+    pub stream_number: u16,
+    // Mandatory(1bit), Reserved(15bits)
+    pub priority_flags: u16,
+}
+
+impl PriorityRecord {
+    // 帯域が足りない場合でも削除してはいけないストリームかどうか
+    pub fn is_mandatory(&self) -> bool {
+        self.priority_flags & PRIORITY_FLAG_MANDATORY != 0
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct StreamPrioritizationObject<'a> {
+    // D4FED15B-88D3-454F-81F0-ED5C45999E24
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // Priority Records の総数
+    pub priority_records_count: u16,
+    // 優先度の高い順
+    pub priority_records: Vec<PriorityRecord>,
+}
+
+impl<'a> StreamPrioritizationObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> StreamPrioritizationObject<'static> {
+        StreamPrioritizationObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            priority_records_count: self.priority_records_count,
+            priority_records: self.priority_records,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BandwidthSharingObject<'a> {
+    // A69609E6-517B-11D2-B6AF-00C04FD908E9
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // GUID. Exclusive, Partial
+    pub sharing_type: Cow<'a, [u8]>,
+    // ストリーム全体で使用する帯域 (bits/sec)
+    pub data_bitrate: u32,
+    // バッファのサイズ (ミリ秒)
+    pub buffer_size: u32,
+    // Stream Numbers の総数
+    pub stream_numbers_count: u16,
+    // 帯域を共有するストリーム
+    pub stream_numbers: Vec<u16>,
+}
+
+impl<'a> BandwidthSharingObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> BandwidthSharingObject<'static> {
+        BandwidthSharingObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            sharing_type: Cow::Owned(self.sharing_type.into_owned()),
+            data_bitrate: self.data_bitrate,
+            buffer_size: self.buffer_size,
+            stream_numbers_count: self.stream_numbers_count,
+            stream_numbers: self.stream_numbers,
+        }
+    }
+}
+
+named!(
+    parse_priority_record<PriorityRecord>,
+    do_parse!(
+        stream_number: le_u16
+            >> priority_flags: le_u16
+            >> (PriorityRecord {
+                stream_number,
+                priority_flags,
+            })
+    )
+);
+
+named!(
+    pub parse_stream_prioritization_object<StreamPrioritizationObject>,
+    do_parse!(
+        object_size: le_u64
+            >> priority_records_count: le_u16
+            >> priority_records:
+                many_m_n!(
+                    priority_records_count as usize,
+                    priority_records_count as usize,
+                    parse_priority_record
+                )
+            >> (StreamPrioritizationObject {
+                object_id: Cow::Borrowed(&STREAM_PRIORITIZATION_OBJECT_GUID[..]),
+                object_size,
+                priority_records_count,
+                priority_records,
+            })
+    )
+);
+
+named!(
+    pub parse_bandwidth_sharing_object<BandwidthSharingObject>,
+    do_parse!(
+        object_size: le_u64
+            >> sharing_type: take!(16)
+            >> data_bitrate: le_u32
+            >> buffer_size: le_u32
+            >> stream_numbers_count: le_u16
+            >> stream_numbers:
+                many_m_n!(
+                    stream_numbers_count as usize,
+                    stream_numbers_count as usize,
+                    le_u16
+                )
+            >> (BandwidthSharingObject {
+                object_id: Cow::Borrowed(&BANDWIDTH_SHARING_OBJECT_GUID[..]),
+                object_size,
+                sharing_type: Cow::Borrowed(sharing_type),
+                data_bitrate,
+                buffer_size,
+                stream_numbers_count,
+                stream_numbers,
+            })
+    )
+);
+
+#[test]
+fn parse_stream_prioritization_object_test() {
+    let input = [34, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2, 0, 1, 0, 1, 0, 0, 0];
+    let (remain, stream_prioritization_object) =
+        parse_stream_prioritization_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(
+        stream_prioritization_object.priority_records[0].stream_number,
+        2
+    );
+    assert!(stream_prioritization_object.priority_records[0].is_mandatory());
+    assert!(!stream_prioritization_object.priority_records[1].is_mandatory());
+}
+
+#[test]
+fn parse_bandwidth_sharing_object_test() {
+    let mut input = vec![54, 0, 0, 0, 0, 0, 0, 0];
+    input.extend_from_slice(&crate::BANDWIDTH_SHARING_TYPE_EXCLUSIVE_GUID);
+    input.extend_from_slice(&[0x00, 0xE8, 0x03, 0x00, 0xB8, 0x0B, 0, 0, 2, 0, 3, 0, 4, 0]);
+    let (remain, bandwidth_sharing_object) = parse_bandwidth_sharing_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(bandwidth_sharing_object.data_bitrate, 256_000);
+    assert_eq!(bandwidth_sharing_object.buffer_size, 3000);
+    assert_eq!(bandwidth_sharing_object.stream_numbers, vec![3, 4]);
+}
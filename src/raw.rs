@@ -0,0 +1,91 @@
+use std::borrow::Cow;
+
+use nom::{le_u64, IResult};
+
+// 解析しなかったオブジェクト. ベンダー独自のオブジェクトの参照や、そのままの再書き込みに使う
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RawObject<'a> {
+    // オブジェクトの GUID
+    pub guid: Cow<'a, [u8]>,
+    // parse_asf に渡した入力の先頭からの位置
+    pub offset: u64,
+    // GUID と Object Size を含むオブジェクト全体
+    pub bytes: Cow<'a, [u8]>,
+}
+
+impl<'a> RawObject<'a> {
+    // オブジェクト全体のサイズ
+    pub fn object_size(&self) -> u64 {
+        self.bytes.len() as u64
+    }
+
+    // GUID と Object Size に続くデータ
+    pub fn data(&self) -> &[u8] {
+        &self.bytes[24..]
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> RawObject<'static> {
+        RawObject {
+            guid: Cow::Owned(self.guid.into_owned()),
+            offset: self.offset,
+            bytes: Cow::Owned(self.bytes.into_owned()),
+        }
+    }
+}
+
+named!(
+    parse_raw_object_header<(&[u8], u64)>,
+    pair!(take!(16), verify!(le_u64, |v: u64| v >= 24))
+);
+
+// GUID から始まるオブジェクトを Object Size の分だけ切り出す. offset はオブジェクトの位置
+pub fn parse_raw_object(input: &[u8], offset: u64) -> IResult<&[u8], RawObject<'_>> {
+    let (_, (guid, object_size)) = try_parse!(input, parse_raw_object_header);
+    let (remain, bytes) = try_parse!(input, take!(object_size));
+    Ok((
+        remain,
+        RawObject {
+            guid: Cow::Borrowed(guid),
+            offset,
+            bytes: Cow::Borrowed(bytes),
+        },
+    ))
+}
+
+#[test]
+fn parse_raw_object_test() {
+    let mut input = vec![0xAB; 16];
+    input.extend_from_slice(&[26, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3]);
+    let (remain, raw_object) = parse_raw_object(&input, 100).unwrap();
+
+    assert_eq!(remain, &[3]);
+    assert_eq!(raw_object.guid, Cow::Borrowed(&[0xAB; 16][..]));
+    assert_eq!(raw_object.offset, 100);
+    assert_eq!(raw_object.object_size(), 26);
+    assert_eq!(raw_object.data(), &[1, 2]);
+
+    input[16] = 23;
+    assert!(parse_raw_object(&input, 0).is_err());
+}
+
+#[test]
+fn unknown_objects_test() {
+    let mut input = include_bytes!("../assets/kte.asf").to_vec();
+    let offset = input.len() as u64;
+    input.extend_from_slice(&[0xCD; 16]);
+    input.extend_from_slice(&28u64.to_le_bytes());
+    input.extend_from_slice(&[1, 2, 3, 4]);
+    let (remain, asf) = crate::parse_asf(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(asf.unknown_objects.len(), 1);
+    assert_eq!(asf.unknown_objects[0].offset, offset);
+    assert_eq!(asf.unknown_objects[0].data(), &[1, 2, 3, 4]);
+
+    // 途中で途切れているオブジェクトは残りの入力として返す
+    input.truncate(input.len() - 1);
+    let (remain, asf) = crate::parse_asf(&input).unwrap();
+    assert_eq!(remain.len(), 27);
+    assert!(asf.unknown_objects.is_empty());
+}
@@ -0,0 +1,1054 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::time::Duration;
+
+use crate::index::SimpleIndexBuilder;
+use crate::packet::DataPacket;
+use crate::script::CommandType;
+use crate::stream::StreamKind;
+use crate::{DataObject, Error, Result, StreamPropertiesObject, ASF};
+
+impl<'a> ASF<'a> {
+    // データパケットを edit で書き換えて Data Object を作り直す. ペイロードが無くなったパケットは取り除く.
+    // Simple Index Object は残った映像ストリームごとに、File Properties Object の Play Duration までを作り直す.
+    // 戻り値は元のパケットごとの新しいパケットの番号
+    fn rewrite_packets<F: FnMut(&mut DataPacket)>(
+        &mut self,
+        mut edit: F,
+    ) -> Result<Vec<Option<u32>>> {
+        let mut index_builders: Vec<(u8, SimpleIndexBuilder)> =
+            if self.simple_index_objects.is_empty() {
+                Vec::new()
+            } else {
+                self.stream_props_objects
+                    .iter()
+                    .filter(|v| v.stream_kind() == StreamKind::Video)
+                    .map(|v| (v.stream_number(), SimpleIndexBuilder::new()))
+                    .collect()
+            };
+        let (file_props_object, data_object) =
+            match (&mut self.file_props_object, &self.data_object) {
+                (Some(file_props_object), Some(data_object)) => (file_props_object, data_object),
+                _ => return Ok(Vec::new()),
+            };
+        let packet_size = file_props_object.min_data_packet_size;
+        let mut packet_numbers = Vec::new();
+        let mut data_packets = Vec::new();
+        let mut total_data_packets = 0u64;
+        for packet in data_object.packets(packet_size) {
+            let mut packet = packet?;
+            edit(&mut packet);
+            if packet.payloads.is_empty() {
+                packet_numbers.push(None);
+                continue;
+            }
+            for payload in &packet.payloads {
+                if let Some((_, builder)) = index_builders
+                    .iter_mut()
+                    .find(|v| v.0 == payload.stream_number)
+                {
+                    builder.push_payload(payload, total_data_packets as u32);
+                }
+            }
+            packet.write_to(&mut data_packets, packet_size)?;
+            packet_numbers.push(Some(total_data_packets as u32));
+            total_data_packets += 1;
+        }
+        let data_object = DataObject {
+            object_id: Cow::Owned(data_object.object_id.to_vec()),
+            object_size: 50 + data_packets.len() as u64,
+            file_id: Cow::Owned(data_object.file_id.to_vec()),
+            total_data_packets,
+            reserved: data_object.reserved,
+            data_packets: Cow::Owned(data_packets),
+        };
+        file_props_object.data_packets_count = total_data_packets;
+
+        let interval = self
+            .simple_index_objects
+            .first()
+            .map_or(0, |v| v.index_entry_time_interval);
+        self.simple_index_objects = index_builders
+            .iter()
+            .map(|(_, builder)| {
+                builder.build(
+                    &file_props_object.file_id,
+                    interval,
+                    file_props_object.play_duration,
+                )
+            })
+            .collect();
+        self.data_object = Some(Box::new(data_object));
+        Ok(packet_numbers)
+    }
+
+    // Marker Object のオフセットを、rewrite_packets の戻り値に従って元のパケット以降で
+    // 最初に残ったパケットを指すように直す. 後ろに残ったパケットが無い場合は最後のパケットを指す
+    fn remap_marker_offsets(&mut self, packet_numbers: &[Option<u32>]) {
+        let packet_size = self
+            .file_props_object
+            .as_ref()
+            .map_or(0, |v| u64::from(v.min_data_packet_size));
+        let packets_count = packet_numbers.iter().flatten().count() as u32;
+        if let Some(ref mut v) = self.marker_object {
+            for marker in &mut v.markers {
+                let packet_number = marker.offset.checked_div(packet_size).unwrap_or(0) as usize;
+                let new_packet_number = packet_numbers
+                    .iter()
+                    .skip(packet_number)
+                    .find_map(|&v| v)
+                    .unwrap_or(packets_count.saturating_sub(1));
+                marker.offset = u64::from(new_packet_number) * packet_size;
+            }
+        }
+    }
+
+    // drop_streams のストリームを取り除いたファイルを作る. データパケットを書き直し、
+    // ストリームに関するオブジェクトと Simple Index Object をそれに合わせる.
+    // サイズや総数のフィールドは write_to で書き込む内容から計算する
+    pub fn remux(mut self, drop_streams: &[u8]) -> Result<ASF<'a>> {
+        for &stream_number in drop_streams {
+            if !self
+                .stream_props_objects
+                .iter()
+                .any(|v| v.stream_number() == stream_number)
+            {
+                return Err(Error::InvalidStreamNumber(stream_number));
+            }
+        }
+        let keep =
+            |stream_number: u16| !drop_streams.iter().any(|&v| u16::from(v) == stream_number);
+
+        // 取り除くストリームの分だけ最大ビットレートを減らす
+        let dropped_bitrate = self.stream_bitrate_props_object.as_ref().map_or(0, |v| {
+            v.bitrate_records
+                .iter()
+                .filter(|v| !keep(u16::from(v.stream_number())))
+                .fold(0u32, |sum, v| sum.saturating_add(v.average_bitrate))
+        });
+        if let Some(ref mut v) = self.file_props_object {
+            v.max_bitrate = v.max_bitrate.saturating_sub(dropped_bitrate);
+        }
+        self.stream_props_objects
+            .retain(|v| keep(u16::from(v.stream_number())));
+        if let Some(ref mut v) = self.stream_bitrate_props_object {
+            v.bitrate_records
+                .retain(|v| keep(u16::from(v.stream_number())));
+        }
+        // 選択肢が1つ以下になった排他関係は意味を持たない
+        for v in &mut self.bitrate_mutual_exclusion_objects {
+            v.stream_numbers.retain(|&v| keep(v));
+        }
+        self.bitrate_mutual_exclusion_objects
+            .retain(|v| v.stream_numbers.len() > 1);
+        if let Some(ref mut header_extension_object) = self.header_extension_object {
+            header_extension_object
+                .extended_stream_props_objects
+                .retain(|v| keep(v.stream_number));
+            for v in &mut header_extension_object.advanced_mutual_exclusion_objects {
+                v.stream_numbers.retain(|&v| keep(v));
+            }
+            header_extension_object
+                .advanced_mutual_exclusion_objects
+                .retain(|v| v.stream_numbers.len() > 1);
+            for v in &mut header_extension_object.group_mutual_exclusion_objects {
+                for record in &mut v.records {
+                    record.stream_numbers.retain(|&v| keep(v));
+                }
+                v.records.retain(|v| !v.stream_numbers.is_empty());
+            }
+            header_extension_object
+                .group_mutual_exclusion_objects
+                .retain(|v| v.records.len() > 1);
+            if let Some(ref mut v) = header_extension_object.stream_prioritization_object {
+                v.priority_records.retain(|v| keep(v.stream_number));
+            }
+            for v in &mut header_extension_object.bandwidth_sharing_objects {
+                v.stream_numbers.retain(|&v| keep(v));
+            }
+            header_extension_object
+                .bandwidth_sharing_objects
+                .retain(|v| !v.stream_numbers.is_empty());
+            if let Some(ref mut v) = header_extension_object.metadata_library_object {
+                v.description_records
+                    .retain(|v| v.stream_number == 0 || keep(v.stream_number));
+            }
+        }
+
+        let packet_numbers = self.rewrite_packets(|packet| {
+            packet.payloads.retain(|v| keep(u16::from(v.stream_number)));
+        })?;
+        self.remap_marker_offsets(&packet_numbers);
+        Ok(self)
+    }
+
+    // プリロールを除いた表示時間 start から end までを切り出す. 開始位置は start 以前で最後の
+    // 映像のキーフレームに合わせ、最初のメディアオブジェクトがプリロールの直後に再生されるように時間をずらす
+    pub fn cut(mut self, start: Duration, end: Duration) -> Result<ASF<'a>> {
+        if start >= end {
+            return Err(Error::InvalidTimeRange(start, end));
+        }
+        let preroll = self
+            .file_props_object
+            .as_ref()
+            .map_or(0, |v| v.preoll.min(u64::from(u32::MAX)) as u32);
+        let start_time =
+            (start.as_millis() as u64 + u64::from(preroll)).min(u64::from(u32::MAX)) as u32;
+        let end_time =
+            (end.as_millis() as u64 + u64::from(preroll)).min(u64::from(u32::MAX)) as u32;
+
+        let video_streams: Vec<u8> = self
+            .stream_props_objects
+            .iter()
+            .filter(|v| v.stream_kind() == StreamKind::Video)
+            .map(StreamPropertiesObject::stream_number)
+            .collect();
+        let mut key_frame_times = Vec::new();
+        if let Some(packets) = self.packets() {
+            for packet in packets {
+                for payload in &packet?.payloads {
+                    if payload.key_frame
+                        && video_streams.contains(&payload.stream_number)
+                        && (payload.is_compressed() || payload.offset_into_media_object == 0)
+                    {
+                        key_frame_times.extend(payload.presentation_time());
+                    }
+                }
+            }
+        }
+        let cut_start = key_frame_times
+            .iter()
+            .filter(|&&v| v <= start_time)
+            .max()
+            .or_else(|| key_frame_times.iter().min())
+            .copied()
+            .unwrap_or(start_time);
+        if cut_start >= end_time {
+            return Err(Error::InvalidTimeRange(start, end));
+        }
+        // 切り出した範囲の表示時間をずらす量(ミリ秒)
+        let shift = cut_start.saturating_sub(preroll);
+        let in_range = |time: u32| time >= cut_start && time < end_time;
+
+        if let Some(ref mut v) = self.file_props_object {
+            v.play_duration = v
+                .play_duration
+                .min(u64::from(end_time) * 10_000)
+                .saturating_sub(u64::from(shift) * 10_000);
+        }
+        if let Some(ref mut v) = self.script_command_object {
+            v.commands.retain(|v| in_range(v.presentation_time));
+            for command in &mut v.commands {
+                command.presentation_time -= shift;
+            }
+        }
+
+        // 送信時間は最初に残ったパケットが 0 になるようにずらす
+        let mut send_shift = None;
+        let mut send_end = 0u32;
+        let packet_numbers = self.rewrite_packets(|packet| {
+            packet
+                .payloads
+                .retain(|v| v.presentation_time().is_some_and(in_range));
+            if packet.payloads.is_empty() {
+                return;
+            }
+            for payload in &mut packet.payloads {
+                if let Some(presentation_time) = payload.presentation_time() {
+                    payload.set_presentation_time(presentation_time - shift);
+                }
+            }
+            let send_shift = *send_shift.get_or_insert(packet.send_time);
+            packet.send_time = packet.send_time.saturating_sub(send_shift);
+            send_end = send_end.max(packet.send_time + u32::from(packet.duration));
+        })?;
+
+        if let Some(ref mut v) = self.file_props_object {
+            v.send_duration = u64::from(send_end) * 10_000;
+        }
+        if let Some(ref mut v) = self.marker_object {
+            v.markers.retain(|v| {
+                in_range((v.presentation_time / 10_000).min(u64::from(u32::MAX)) as u32)
+            });
+            for marker in &mut v.markers {
+                marker.presentation_time -= u64::from(shift) * 10_000;
+                marker.send_time = marker.send_time.saturating_sub(send_shift.unwrap_or(shift));
+            }
+        }
+        self.remap_marker_offsets(&packet_numbers);
+        Ok(self)
+    }
+}
+
+// ミリ秒の時間を u32 にする. 表せない場合は Error::InvalidTimestamp
+fn checked_millis(time: u64) -> Result<u32> {
+    u32::try_from(time).map_err(|_| Error::InvalidTimestamp(Duration::from_millis(time)))
+}
+
+// 連結するファイルごとのパケット数、プリロール、表示時間をずらす量(ミリ秒)
+struct ConcatFile {
+    packets_count: usize,
+    preroll: u32,
+    offset: u32,
+}
+
+impl<'a> ASF<'a> {
+    // other のストリームの構成がこのファイルと同じかどうか. ストリーム番号、種類、Type-Specific Data と、
+    // Audio Spread の並び替えに使う Error Correction Type, Error Correction Data を比べる
+    fn has_same_streams(&self, other: &ASF) -> bool {
+        self.stream_props_objects.len() == other.stream_props_objects.len()
+            && self
+                .stream_props_objects
+                .iter()
+                .zip(&other.stream_props_objects)
+                .all(|(a, b)| {
+                    a.stream_number() == b.stream_number()
+                        && a.stream_type == b.stream_type
+                        && a.type_specific_data == b.type_specific_data
+                        && a.error_correction_type == b.error_correction_type
+                        && a.error_correction_data == b.error_correction_data
+                })
+    }
+
+    // others をこのファイルの後ろに連結する. 表示時間と送信時間は前のファイルの終端から続くようにずらし、
+    // メディアオブジェクトの番号はストリームごとに続けて振り直す
+    pub fn concat<I: IntoIterator<Item = ASF<'a>>>(mut self, others: I) -> Result<ASF<'a>> {
+        let others: Vec<ASF<'a>> = others.into_iter().collect();
+        let packet_size = self
+            .file_props_object
+            .as_ref()
+            .map_or(0, |v| v.min_data_packet_size);
+        for (i, other) in others.iter().enumerate() {
+            if !self.has_same_streams(other) {
+                return Err(Error::IncompatibleStreams(i + 1));
+            }
+            let other_packet_size = other
+                .file_props_object
+                .as_ref()
+                .map_or(0, |v| v.min_data_packet_size);
+            if other_packet_size != packet_size {
+                return Err(Error::IncompatibleStreams(i + 1));
+            }
+        }
+        // 先頭のファイルに Data Object が無いと、後ろのファイルのパケットを入れる場所が無い
+        if self.data_object.is_none() && others.iter().any(|v| v.data_object.is_some()) {
+            return Err(Error::IncompatibleStreams(0));
+        }
+
+        let mut files = Vec::new();
+        let mut offset = 0u32;
+        for asf in Some(&self).into_iter().chain(&others) {
+            let packets_count = asf
+                .data_object
+                .as_ref()
+                .map_or(0, |v| v.data_packets.len() / packet_size.max(1) as usize);
+            let (preroll, playback_duration) = match asf.file_props_object {
+                Some(ref v) => (
+                    checked_millis(v.preoll)?,
+                    v.playback_duration().unwrap_or_default().as_millis(),
+                ),
+                None => (0, 0),
+            };
+            files.push(ConcatFile {
+                packets_count,
+                preroll,
+                offset,
+            });
+            offset = checked_millis(
+                u64::try_from(u128::from(offset) + playback_duration).unwrap_or(u64::MAX),
+            )?;
+        }
+        let preroll = files[0].preroll;
+        // 先頭のファイルのプリロールを基準にした表示時間への変換. 連結した時間が u32 のミリ秒を超える場合はエラー
+        let presentation_time = |file: &ConcatFile, time: u32| {
+            checked_millis(
+                (u64::from(time) + u64::from(preroll) + u64::from(file.offset))
+                    .saturating_sub(u64::from(file.preroll)),
+            )
+        };
+
+        let mut data_packets = Vec::new();
+        if let Some(ref v) = self.data_object {
+            data_packets.extend_from_slice(&v.data_packets);
+        }
+        for other in &others {
+            if let Some(ref v) = other.data_object {
+                data_packets.extend_from_slice(&v.data_packets);
+            }
+        }
+        let total_data_packets = files.iter().map(|v| v.packets_count as u64).sum();
+        if let Some(ref mut v) = self.data_object {
+            v.data_packets = Cow::Owned(data_packets);
+            v.total_data_packets = total_data_packets;
+        }
+        if let Some(ref mut v) = self.file_props_object {
+            v.play_duration = (u64::from(preroll) + u64::from(offset)) * 10_000;
+            v.max_bitrate = others
+                .iter()
+                .filter_map(|v| v.file_props_object.as_ref())
+                .fold(v.max_bitrate, |max, v| max.max(v.max_bitrate));
+        }
+
+        let mut file_index = 0;
+        let mut packets_left = files[0].packets_count;
+        // 送信時間は前のファイルの最後のパケットより後にする
+        let mut send_offset = 0u64;
+        let mut send_end = 0u64;
+        // ストリームごとの、元の番号に足す値と次のメディアオブジェクトの番号
+        let mut media_object_numbers: Vec<(u8, u32, u32)> = Vec::new();
+        let mut current_file = Vec::new();
+        // 時間が u32 のミリ秒で表せなくなった場合のエラー
+        let mut error = None;
+        self.rewrite_packets(|packet| {
+            if error.is_some() {
+                return;
+            }
+            while packets_left == 0 && file_index + 1 < files.len() {
+                file_index += 1;
+                packets_left = files[file_index].packets_count;
+                send_offset = u64::from(files[file_index].offset).max(send_end);
+                current_file.clear();
+            }
+            packets_left = packets_left.saturating_sub(1);
+            let file = &files[file_index];
+            // 番号は Media Object Number Length Type で表せる範囲で振り直す
+            let mask = match (packet.property_flags >> 4) & 0x03 {
+                0 => 0,
+                1 => 0xFF,
+                2 => 0xFFFF,
+                _ => u32::MAX,
+            };
+            for payload in &mut packet.payloads {
+                if let Some(time) = payload.presentation_time() {
+                    match presentation_time(file, time) {
+                        Ok(time) => payload.set_presentation_time(time),
+                        Err(e) => {
+                            error = Some(e);
+                            return;
+                        }
+                    }
+                }
+                // 圧縮ペイロードはサブペイロードごとに番号を使う
+                let count = if payload.is_compressed() {
+                    payload.sub_payloads().count() as u32
+                } else {
+                    1
+                };
+                let index = match media_object_numbers
+                    .iter()
+                    .position(|v| v.0 == payload.stream_number)
+                {
+                    Some(index) => index,
+                    None => {
+                        media_object_numbers.push((payload.stream_number, 0, 0));
+                        media_object_numbers.len() - 1
+                    }
+                };
+                let entry = &mut media_object_numbers[index];
+                // ファイルの最初のペイロードで、続きの番号になるように足す値を決める
+                if !current_file.contains(&payload.stream_number) {
+                    current_file.push(payload.stream_number);
+                    entry.1 = if file_index == 0 {
+                        0
+                    } else {
+                        entry.2.wrapping_sub(payload.media_object_number)
+                    };
+                }
+                payload.media_object_number =
+                    payload.media_object_number.wrapping_add(entry.1) & mask;
+                if payload.is_compressed() || payload.offset_into_media_object == 0 {
+                    entry.2 = payload.media_object_number.wrapping_add(count);
+                }
+            }
+            match checked_millis(u64::from(packet.send_time) + send_offset) {
+                Ok(send_time) => {
+                    packet.send_time = send_time;
+                    send_end = send_end.max(u64::from(send_time) + u64::from(packet.duration));
+                }
+                Err(e) => error = Some(e),
+            }
+        })?;
+        if let Some(e) = error {
+            return Err(e);
+        }
+        if let Some(ref mut v) = self.file_props_object {
+            v.send_duration = send_end * 10_000;
+        }
+
+        // マーカーとスクリプトコマンドも時間をずらして連結する
+        let mut first_packet_number = files[0].packets_count as u64;
+        for (other, file) in others.into_iter().zip(&files[1..]) {
+            if let Some(mut marker_object) = other.marker_object {
+                let mut markers = std::mem::take(&mut marker_object.markers);
+                for marker in &mut markers {
+                    marker.presentation_time = u64::from(presentation_time(
+                        file,
+                        checked_millis(marker.presentation_time / 10_000)?,
+                    )?) * 10_000;
+                    marker.send_time =
+                        checked_millis(u64::from(marker.send_time) + u64::from(file.offset))?;
+                    marker.offset += first_packet_number * u64::from(packet_size);
+                }
+                match self.marker_object {
+                    Some(ref mut v) => v.markers.extend(markers),
+                    None => {
+                        marker_object.markers = markers;
+                        self.marker_object = Some(marker_object);
+                    }
+                }
+            }
+            if let Some(script_command_object) = other.script_command_object {
+                let mut script_command_object = *script_command_object;
+                let commands = std::mem::take(&mut script_command_object.commands);
+                let command_types = std::mem::take(&mut script_command_object.command_types);
+                let target = self
+                    .script_command_object
+                    .get_or_insert(Box::new(script_command_object));
+                for mut command in commands {
+                    // Command Types は名前で対応させる
+                    let command_type = match command_types.get(command.type_index as usize) {
+                        Some(v) => v,
+                        None => continue,
+                    };
+                    let type_index = match target
+                        .command_types
+                        .iter()
+                        .position(|v| v.command_type_name == command_type.command_type_name)
+                    {
+                        Some(i) => i,
+                        None => {
+                            target.command_types.push(CommandType {
+                                command_type_name_length: command_type.command_type_name_length,
+                                command_type_name: command_type.command_type_name.clone(),
+                            });
+                            target.command_types.len() - 1
+                        }
+                    };
+                    command.type_index = type_index as u16;
+                    command.presentation_time = presentation_time(file, command.presentation_time)?;
+                    target.commands.push(command);
+                }
+            }
+            first_packet_number += file.packets_count as u64;
+        }
+        Ok(self)
+    }
+}
+
+// (パケットの番号, 表示時間(ミリ秒)) ごとのマーカーを持つ Marker Object
+#[cfg(test)]
+fn test_marker_object(
+    packet_size: u32,
+    markers: &[(u32, u32)],
+) -> crate::marker::MarkerObject<'static> {
+    crate::marker::MarkerObject {
+        object_id: Cow::Borrowed(&crate::MARKER_OBJECT_GUID),
+        object_size: 0,
+        reserved: Cow::Owned(vec![
+            0x20, 0xDB, 0xFE, 0x4C, 0xF6, 0x75, 0xCF, 0x11, 0x9C, 0x0F, 0x00, 0xA0, 0xC9, 0x03,
+            0x49, 0xCB,
+        ]),
+        markers_count: 0,
+        reserved_2: 0,
+        name_length: 0,
+        name: Cow::Borrowed(&[]),
+        markers: markers
+            .iter()
+            .map(|&(packet_number, time)| crate::marker::MarkerEntry {
+                offset: u64::from(packet_number) * u64::from(packet_size),
+                presentation_time: u64::from(time) * 10_000,
+                entry_length: 14,
+                send_time: time,
+                flags: 0,
+                marker_description_length: 1,
+                marker_description: Cow::Borrowed(&[0, 0]),
+            })
+            .collect(),
+    }
+}
+
+// 名前が types の Command Types と、(表示時間(ミリ秒), Type Index, 名前) ごとのコマンドを持つ Script Command Object
+#[cfg(test)]
+fn test_script_command_object(
+    types: &[&str],
+    commands: &[(u32, u16, &str)],
+) -> crate::script::ScriptCommandObject<'static> {
+    crate::script::ScriptCommandObject {
+        object_id: Cow::Borrowed(&crate::SCRIPT_COMMAND_OBJECT_GUID),
+        object_size: 0,
+        reserved: Cow::Owned(vec![
+            227, 203, 26, 75, 11, 16, 208, 17, 163, 155, 0, 160, 201, 3, 72, 246,
+        ]),
+        commands_count: 0,
+        command_types_count: 0,
+        command_types: types
+            .iter()
+            .map(|name| CommandType {
+                command_type_name_length: name.len() as u16 + 1,
+                command_type_name: Cow::Owned(crate::utf16::encode(&format!("{}\0", name))),
+            })
+            .collect(),
+        commands: commands
+            .iter()
+            .map(
+                |&(presentation_time, type_index, name)| crate::script::ScriptCommand {
+                    presentation_time,
+                    type_index,
+                    command_name_length: name.len() as u16 + 1,
+                    command_name: Cow::Owned(crate::utf16::encode(&format!("{}\0", name))),
+                },
+            )
+            .collect(),
+    }
+}
+
+#[test]
+fn remux_test() {
+    let input = include_bytes!("../assets/kte.asf");
+
+    // 何も取り除かない場合は元のファイルと同じになる
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    assert_eq!(asf.remux(&[]).unwrap().to_bytes(), &input[..]);
+
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    match asf.remux(&[5]) {
+        Err(Error::InvalidStreamNumber(5)) => {}
+        _ => panic!(),
+    }
+
+    // 音声ストリームを取り除く
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let video_objects: Vec<_> = asf
+        .media_objects()
+        .unwrap()
+        .map(|v| v.unwrap())
+        .filter(|v| v.stream_number == 2)
+        .collect();
+    let max_bitrate = asf.file_props_object.as_ref().unwrap().max_bitrate;
+    let audio_bitrate = asf.bitrate_record(1).unwrap().average_bitrate;
+    let output = asf.remux(&[1]).unwrap().to_bytes();
+    let (remain, asf) = crate::parse_asf(&output).unwrap();
+    assert!(remain.is_empty());
+    let file_props_object = asf.file_props_object.as_ref().unwrap();
+    let data_object = asf.data_object.as_ref().unwrap();
+    assert_eq!(file_props_object.file_size, output.len() as u64);
+    assert_eq!(
+        file_props_object.data_packets_count,
+        data_object.total_data_packets
+    );
+    assert!(data_object.total_data_packets < 153);
+    assert_eq!(file_props_object.max_bitrate, max_bitrate - audio_bitrate);
+    assert_eq!(asf.stream_props_objects.len(), 1);
+    assert_eq!(asf.stream_props_objects[0].stream_number(), 2);
+    assert!(asf.bitrate_record(1).is_none());
+    assert!(asf.bitrate_record(2).is_some());
+    assert!(asf.extended_stream_props_object(1).is_none());
+    assert!(asf.extended_stream_props_object(2).is_some());
+    assert!(asf.validate_streams().is_ok());
+    let media_objects: Vec<_> = asf.media_objects().unwrap().map(|v| v.unwrap()).collect();
+    assert_eq!(media_objects, video_objects);
+
+    // 索引はキーフレームの先頭を含むパケットを指す
+    let packets: Vec<_> = asf.packets().unwrap().map(|v| v.unwrap()).collect();
+    assert_eq!(asf.simple_index_objects.len(), 1);
+    for entry in &asf.simple_index_objects[0].index_entries {
+        assert!(packets[entry.packet_number as usize]
+            .payloads
+            .iter()
+            .any(|v| v.key_frame && v.offset_into_media_object == 0));
+    }
+
+    // マーカーは残ったストリームのパケットを新しい番号で指す.
+    // 音声だけのパケットは末尾にあるので、映像ストリームを取り除くとパケットの番号が変わる
+    for &(drop_stream, keep_stream) in &[(1u8, 2u8), (2, 1)] {
+        let (_, mut asf) = crate::parse_asf(input).unwrap();
+        let packet_size = u64::from(asf.file_props_object.as_ref().unwrap().min_data_packet_size);
+        let mut markers = Vec::new();
+        let mut dropped_markers = Vec::new();
+        for (packet_number, packet) in asf.packets().unwrap().enumerate() {
+            let payloads = packet.unwrap().payloads;
+            if let Some(payload) = payloads
+                .iter()
+                .find(|v| v.stream_number == keep_stream && v.offset_into_media_object == 0)
+            {
+                markers.push((packet_number as u32, payload.presentation_time().unwrap()));
+            } else if payloads.iter().all(|v| v.stream_number == drop_stream) {
+                let time = payloads[0].presentation_time().unwrap();
+                dropped_markers.push((packet_number as u32, time));
+            }
+        }
+        let all_markers: Vec<_> = markers.iter().chain(&dropped_markers).cloned().collect();
+        asf.marker_object = Some(Box::new(test_marker_object(
+            packet_size as u32,
+            &all_markers,
+        )));
+        let output = asf.remux(&[drop_stream]).unwrap().to_bytes();
+        let (_, asf) = crate::parse_asf(&output).unwrap();
+        let packets: Vec<_> = asf.packets().unwrap().map(|v| v.unwrap()).collect();
+        let marker_object = asf.marker_object.as_ref().unwrap();
+        assert_eq!(marker_object.markers.len(), all_markers.len());
+        let (kept, dropped) = marker_object.markers.split_at(markers.len());
+        for marker in kept {
+            let packet = &packets[(marker.offset / packet_size) as usize];
+            assert!(packet
+                .payloads
+                .iter()
+                .any(|v| v.stream_number == keep_stream
+                    && v.offset_into_media_object == 0
+                    && v.presentation_time() == Some((marker.presentation_time / 10_000) as u32)));
+        }
+        // パケットごと無くなったマーカーも Data Object の中を指す
+        for marker in dropped {
+            assert!(marker.offset / packet_size < packets.len() as u64);
+        }
+        if drop_stream == 2 {
+            assert!(
+                kept.iter()
+                    .zip(&markers)
+                    .any(|(v, &(packet_number, _))| v.offset / packet_size
+                        != u64::from(packet_number))
+            );
+        }
+    }
+
+    // 映像ストリームを取り除くと索引も無くなる
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let output = asf.remux(&[2]).unwrap().to_bytes();
+    let (_, asf) = crate::parse_asf(&output).unwrap();
+    assert!(asf.simple_index_objects.is_empty());
+    assert!(asf
+        .media_objects()
+        .unwrap()
+        .all(|v| v.unwrap().stream_number == 1));
+}
+
+#[test]
+fn cut_test() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let preroll = asf.file_props_object.as_ref().unwrap().preoll as u32;
+    let media_objects: Vec<_> = asf.media_objects().unwrap().map(|v| v.unwrap()).collect();
+    let key_frame_times: Vec<u32> = media_objects
+        .iter()
+        .filter(|v| v.stream_number == 2 && v.key_frame)
+        .map(|v| v.presentation_time)
+        .collect();
+
+    match asf.cut(Duration::from_secs(3), Duration::from_secs(3)) {
+        Err(Error::InvalidTimeRange(_, _)) => {}
+        _ => panic!(),
+    }
+
+    // 3秒から8秒まで. 開始位置は3秒以前で最後のキーフレームになる
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    // 映像のメディアオブジェクトの先頭ごとのマーカーと、範囲の前、中、後ろのスクリプトコマンド
+    let packet_size = u64::from(asf.file_props_object.as_ref().unwrap().min_data_packet_size);
+    let (_, original) = crate::parse_asf(input).unwrap();
+    let packets: Vec<_> = original.packets().unwrap().map(|v| v.unwrap()).collect();
+    let mut markers = Vec::new();
+    for (packet_number, packet) in packets.iter().enumerate() {
+        for payload in &packet.payloads {
+            if payload.stream_number == 2 && payload.offset_into_media_object == 0 {
+                markers.push((packet_number as u32, payload.presentation_time().unwrap()));
+            }
+        }
+    }
+    asf.marker_object = Some(Box::new(test_marker_object(packet_size as u32, &markers)));
+    asf.script_command_object = Some(Box::new(test_script_command_object(
+        &["URL", "CAPTION"],
+        &[
+            (preroll + 500, 0, "http://example.com/"),
+            (preroll + 5000, 1, "Hello"),
+            (preroll + 9000, 1, "Bye"),
+        ],
+    )));
+    let cut_start = *key_frame_times
+        .iter()
+        .filter(|&&v| v <= 3000 + preroll)
+        .max()
+        .unwrap();
+    let shift = cut_start - preroll;
+    let expected: Vec<_> = media_objects
+        .iter()
+        .filter(|v| v.presentation_time >= cut_start && v.presentation_time < 8000 + preroll)
+        .map(|v| crate::media::MediaObject {
+            presentation_time: v.presentation_time - shift,
+            ..v.clone()
+        })
+        .collect();
+    let in_range = |time: u32| time >= cut_start && time < 8000 + preroll;
+    // 送信時間は最初に残るパケットの送信時間の分だけずれる
+    let send_shift = packets
+        .iter()
+        .find(|v| {
+            v.payloads
+                .iter()
+                .any(|v| v.presentation_time().is_some_and(in_range))
+        })
+        .unwrap()
+        .send_time;
+    let output = asf
+        .cut(Duration::from_secs(3), Duration::from_secs(8))
+        .unwrap()
+        .to_bytes();
+    let (remain, asf) = crate::parse_asf(&output).unwrap();
+    assert!(remain.is_empty());
+    let file_props_object = asf.file_props_object.as_ref().unwrap();
+    assert_eq!(file_props_object.file_size, output.len() as u64);
+    assert_eq!(
+        file_props_object.data_packets_count,
+        asf.data_object.as_ref().unwrap().total_data_packets
+    );
+    assert_eq!(
+        file_props_object.playback_duration(),
+        Some(Duration::from_millis(u64::from(8000 + preroll - cut_start)))
+    );
+    let media_objects: Vec<_> = asf.media_objects().unwrap().map(|v| v.unwrap()).collect();
+    assert_eq!(media_objects, expected);
+    // 最初の映像のメディアオブジェクトはキーフレームで、プリロールの直後に再生される
+    let first_video = media_objects.iter().find(|v| v.stream_number == 2).unwrap();
+    assert!(first_video.key_frame);
+    assert_eq!(first_video.presentation_time, preroll);
+
+    // 範囲外のマーカーとスクリプトコマンドは取り除き、残ったものは時間をずらす
+    let packets: Vec<_> = asf.packets().unwrap().map(|v| v.unwrap()).collect();
+    let expected_markers: Vec<u32> = markers
+        .iter()
+        .map(|v| v.1)
+        .filter(|&v| in_range(v))
+        .collect();
+    let marker_object = asf.marker_object.as_ref().unwrap();
+    assert!(!expected_markers.is_empty() && expected_markers.len() < markers.len());
+    assert_eq!(marker_object.markers.len(), expected_markers.len());
+    for (marker, &time) in marker_object.markers.iter().zip(&expected_markers) {
+        assert_eq!(marker.presentation_time, u64::from(time - shift) * 10_000);
+        assert_eq!(marker.send_time, time - send_shift);
+        // オフセットは書き直したパケットのうち、そのマーカーの映像を含むものを指す
+        let packet = &packets[(marker.offset / packet_size) as usize];
+        assert!(packet.payloads.iter().any(|v| v.stream_number == 2
+            && v.offset_into_media_object == 0
+            && v.presentation_time() == Some(time - shift)));
+    }
+    let script_command_object = asf.script_command_object.as_ref().unwrap();
+    assert_eq!(script_command_object.commands.len(), 1);
+    let command = &script_command_object.commands[0];
+    assert_eq!(command.presentation_time, preroll + 5000 - shift);
+    assert_eq!(command.type_index, 1);
+    assert_eq!(command.name(), "Hello");
+
+    // 索引は新しいパケットの番号でキーフレームを指す
+    assert_eq!(packets[0].send_time, 0);
+    let simple_index_object = &asf.simple_index_objects[0];
+    assert_eq!(
+        simple_index_object.index_entries.len() as u64,
+        file_props_object.play_duration.div_ceil(10_000_000) + 1
+    );
+    for entry in &simple_index_object.index_entries {
+        assert!(packets[entry.packet_number as usize]
+            .payloads
+            .iter()
+            .any(|v| v.stream_number == 2 && v.key_frame && v.offset_into_media_object == 0));
+    }
+}
+
+#[test]
+fn concat_test() {
+    let input = include_bytes!("../assets/kte.asf");
+
+    // 1つだけの場合は元のファイルと同じになる
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    assert_eq!(asf.concat(Vec::new()).unwrap().to_bytes(), &input[..]);
+
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let (_, other) = crate::parse_asf(include_bytes!("../assets/320x180_10fps.asf")).unwrap();
+    match asf.concat(vec![other]) {
+        Err(Error::IncompatibleStreams(1)) => {}
+        _ => panic!(),
+    }
+
+    // Audio Spread のパラメーターやパケットサイズが異なるファイルは連結できない
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let (_, same) = crate::parse_asf(input).unwrap();
+    let (_, mut other) = crate::parse_asf(input).unwrap();
+    let mut error_correction_data = other.stream_props_objects[0].error_correction_data.to_vec();
+    error_correction_data[0] = 2;
+    other.stream_props_objects[0].error_correction_data = Cow::Owned(error_correction_data);
+    match asf.concat(vec![same, other]) {
+        Err(Error::IncompatibleStreams(2)) => {}
+        _ => panic!(),
+    }
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let (_, mut other) = crate::parse_asf(input).unwrap();
+    if let Some(ref mut v) = other.file_props_object {
+        v.min_data_packet_size += 1;
+        v.max_data_packet_size += 1;
+    }
+    match asf.concat(vec![other]) {
+        Err(Error::IncompatibleStreams(1)) => {}
+        _ => panic!(),
+    }
+
+    // 先頭のファイルに Data Object が無い
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    asf.data_object = None;
+    let (_, other) = crate::parse_asf(input).unwrap();
+    match asf.concat(vec![other]) {
+        Err(Error::IncompatibleStreams(0)) => {}
+        _ => panic!(),
+    }
+
+    // 連結した時間が u32 のミリ秒で表せない
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    if let Some(ref mut v) = asf.file_props_object {
+        v.play_duration = u64::from(u32::MAX) * 10_000;
+    }
+    let (_, other) = crate::parse_asf(input).unwrap();
+    match asf.concat(vec![other]) {
+        Err(Error::InvalidTimestamp(_)) => {}
+        _ => panic!(),
+    }
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let (_, mut other) = crate::parse_asf(input).unwrap();
+    if let Some(ref mut v) = other.file_props_object {
+        v.preoll = u64::from(u32::MAX) + 1;
+    }
+    match asf.concat(vec![other]) {
+        Err(Error::InvalidTimestamp(_)) => {}
+        _ => panic!(),
+    }
+
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let file_props_object = asf.file_props_object.as_ref().unwrap();
+    let preroll = file_props_object.preoll;
+    let playback_duration = file_props_object.playback_duration().unwrap().as_millis() as u32;
+    let packet_size = u64::from(file_props_object.min_data_packet_size);
+    let media_objects: Vec<_> = asf.media_objects().unwrap().map(|v| v.unwrap()).collect();
+    let mut markers = Vec::new();
+    for (packet_number, packet) in asf.packets().unwrap().enumerate() {
+        for payload in &packet.unwrap().payloads {
+            if payload.stream_number == 2 && payload.offset_into_media_object == 0 {
+                markers.push((packet_number as u32, payload.presentation_time().unwrap()));
+            }
+        }
+    }
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    asf.marker_object = Some(Box::new(test_marker_object(
+        packet_size as u32,
+        &markers[..1],
+    )));
+    asf.script_command_object = Some(Box::new(test_script_command_object(
+        &["URL"],
+        &[(preroll as u32 + 100, 0, "http://example.com/")],
+    )));
+    // 後ろのファイルの Command Types は順序が異なり、名前で対応させる
+    let (_, mut other) = crate::parse_asf(input).unwrap();
+    other.marker_object = Some(Box::new(test_marker_object(
+        packet_size as u32,
+        &markers[1..],
+    )));
+    other.script_command_object = Some(Box::new(test_script_command_object(
+        &["CAPTION", "URL"],
+        &[
+            (preroll as u32 + 200, 1, "http://example.org/"),
+            (preroll as u32 + 300, 0, "Hello"),
+        ],
+    )));
+    let output = asf.concat(vec![other]).unwrap().to_bytes();
+
+    let (remain, asf) = crate::parse_asf(&output).unwrap();
+    assert!(remain.is_empty());
+    let file_props_object = asf.file_props_object.as_ref().unwrap();
+    assert_eq!(file_props_object.file_size, output.len() as u64);
+    assert_eq!(file_props_object.data_packets_count, 306);
+    assert_eq!(
+        file_props_object.play_duration,
+        (preroll + 2 * u64::from(playback_duration)) * 10_000
+    );
+
+    // 後ろのファイルの表示時間は前のファイルの終端から続き、番号もストリームごとに続く
+    let concatenated: Vec<_> = asf.media_objects().unwrap().map(|v| v.unwrap()).collect();
+    assert_eq!(concatenated.len(), media_objects.len() * 2);
+    let (first, second) = concatenated.split_at(media_objects.len());
+    assert_eq!(first, &media_objects[..]);
+    for (a, b) in media_objects.iter().zip(second) {
+        assert_eq!(a.stream_number, b.stream_number);
+        assert_eq!(a.presentation_time + playback_duration, b.presentation_time);
+        assert_eq!(a.key_frame, b.key_frame);
+        assert_eq!(a.data, b.data);
+    }
+    for stream_number in 1..=2 {
+        let numbers: Vec<u32> = concatenated
+            .iter()
+            .filter(|v| v.stream_number == stream_number)
+            .map(|v| v.media_object_number)
+            .collect();
+        for pair in numbers.windows(2) {
+            assert_eq!((pair[0] + 1) % 256, pair[1]);
+        }
+    }
+
+    // 後ろのファイルのマーカーは時間とオフセットをずらし、同じメディアオブジェクトを含むパケットを指す
+    let packets: Vec<_> = asf.packets().unwrap().map(|v| v.unwrap()).collect();
+    let marker_object = asf.marker_object.as_ref().unwrap();
+    assert_eq!(marker_object.markers.len(), markers.len());
+    assert_eq!(
+        marker_object.markers[0].offset,
+        u64::from(markers[0].0) * packet_size
+    );
+    for (marker, &(packet_number, time)) in marker_object.markers.iter().zip(&markers).skip(1) {
+        let time = time + playback_duration;
+        assert_eq!(marker.presentation_time, u64::from(time) * 10_000);
+        assert_eq!(marker.send_time, time);
+        assert_eq!(marker.offset, u64::from(packet_number + 153) * packet_size);
+        assert!(packets[(marker.offset / packet_size) as usize]
+            .payloads
+            .iter()
+            .any(|v| v.stream_number == 2
+                && v.offset_into_media_object == 0
+                && v.presentation_time() == Some(time)));
+    }
+
+    // スクリプトコマンドは時間をずらし、Command Types を名前で付け直す
+    let script_command_object = asf.script_command_object.as_ref().unwrap();
+    let command_types: Vec<String> = script_command_object
+        .command_types
+        .iter()
+        .map(CommandType::name)
+        .collect();
+    assert_eq!(command_types, vec!["URL", "CAPTION"]);
+    let commands: Vec<(u32, u16, String)> = script_command_object
+        .commands
+        .iter()
+        .map(|v| (v.presentation_time, v.type_index, v.name()))
+        .collect();
+    let preroll = preroll as u32;
+    assert_eq!(
+        commands,
+        vec![
+            (preroll + 100, 0, "http://example.com/".to_string()),
+            (
+                preroll + 200 + playback_duration,
+                0,
+                "http://example.org/".to_string()
+            ),
+            (preroll + 300 + playback_duration, 1, "Hello".to_string()),
+        ]
+    );
+
+    // 索引は連結したファイル全体を指す
+    let simple_index_object = &asf.simple_index_objects[0];
+    assert_eq!(
+        simple_index_object.index_entries.len() as u64,
+        file_props_object.play_duration.div_ceil(10_000_000) + 1
+    );
+    assert!(simple_index_object
+        .index_entries
+        .iter()
+        .any(|v| v.packet_number >= 153));
+    for entry in &simple_index_object.index_entries {
+        assert!(packets[entry.packet_number as usize]
+            .payloads
+            .iter()
+            .any(|v| v.stream_number == 2 && v.key_frame && v.offset_into_media_object == 0));
+    }
+}
@@ -0,0 +1,393 @@
+use std::borrow::Cow;
+use std::fmt::Write;
+use std::time::Duration;
+
+use nom::{le_u16, le_u32, le_u64};
+
+use crate::stream::StreamKind;
+use crate::utf16;
+use crate::{Result, ASF, SCRIPT_COMMAND_OBJECT_GUID};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct CommandType<'a> {
+    // Command Type Name の文字数. 終端のヌル文字を含む
+    pub command_type_name_length: u16,
+    // UTF-16LE. "URL", "FILENAME", "CAPTION", ...
+    pub command_type_name: Cow<'a, [u8]>,
+}
+
+impl<'a> CommandType<'a> {
+    pub fn name(&self) -> String {
+        utf16::decode(&self.command_type_name)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> CommandType<'static> {
+        CommandType {
+            command_type_name_length: self.command_type_name_length,
+            command_type_name: Cow::Owned(self.command_type_name.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScriptCommand<'a> {
+    // ミリ秒単位. プリロールを含む
+    pub presentation_time: u32,
+    // Command Types のインデックス
+    pub type_index: u16,
+    // Command Name の文字数. 終端のヌル文字を含む
+    pub command_name_length: u16,
+    // UTF-16LE. URL やキャプションの文字列
+    pub command_name: Cow<'a, [u8]>,
+}
+
+impl<'a> ScriptCommand<'a> {
+    pub fn name(&self) -> String {
+        utf16::decode(&self.command_name)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> ScriptCommand<'static> {
+        ScriptCommand {
+            presentation_time: self.presentation_time,
+            type_index: self.type_index,
+            command_name_length: self.command_name_length,
+            command_name: Cow::Owned(self.command_name.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ScriptCommandObject<'a> {
+    // 1EFB1A30-0B62-11D0-A39B-00A0C90348F6
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // 4B1ACBE3-100B-11D0-A39B-00A0C90348F6
+    pub reserved: Cow<'a, [u8]>,
+    // Commands の総数
+    pub commands_count: u16,
+    // Command Types の総数
+    pub command_types_count: u16,
+    pub command_types: Vec<CommandType<'a>>,
+    pub commands: Vec<ScriptCommand<'a>>,
+}
+
+impl<'a> ScriptCommandObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> ScriptCommandObject<'static> {
+        ScriptCommandObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            reserved: Cow::Owned(self.reserved.into_owned()),
+            commands_count: self.commands_count,
+            command_types_count: self.command_types_count,
+            command_types: self
+                .command_types
+                .into_iter()
+                .map(CommandType::into_owned)
+                .collect(),
+            commands: self
+                .commands
+                .into_iter()
+                .map(ScriptCommand::into_owned)
+                .collect(),
+        }
+    }
+}
+
+// 種類を解決し、プリロールを除いた時間に変換したコマンド
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Command {
+    // プリロールを除いた表示時間
+    pub presentation_time: Duration,
+    // "URL", "FILENAME", "CAPTION", ...
+    pub command_type: String,
+    // コマンドの引数
+    pub parameter: String,
+}
+
+impl Command {
+    pub fn is_caption(&self) -> bool {
+        self.command_type.eq_ignore_ascii_case("CAPTION")
+    }
+}
+
+named!(
+    parse_command_type<CommandType>,
+    do_parse!(
+        command_type_name_length: le_u16
+            >> command_type_name: take!(command_type_name_length as usize * 2)
+            >> (CommandType {
+                command_type_name_length,
+                command_type_name: Cow::Borrowed(command_type_name),
+            })
+    )
+);
+
+named!(
+    parse_script_command<ScriptCommand>,
+    do_parse!(
+        presentation_time: le_u32
+            >> type_index: le_u16
+            >> command_name_length: le_u16
+            >> command_name: take!(command_name_length as usize * 2)
+            >> (ScriptCommand {
+                presentation_time,
+                type_index,
+                command_name_length,
+                command_name: Cow::Borrowed(command_name),
+            })
+    )
+);
+
+named!(
+    pub parse_script_command_object<ScriptCommandObject>,
+    do_parse!(
+        object_size: le_u64
+            >> reserved: take!(16)
+            >> commands_count: le_u16
+            >> command_types_count: le_u16
+            >> command_types:
+                many_m_n!(
+                    command_types_count as usize,
+                    command_types_count as usize,
+                    parse_command_type
+                )
+            >> commands:
+                many_m_n!(
+                    commands_count as usize,
+                    commands_count as usize,
+                    parse_script_command
+                )
+            >> (ScriptCommandObject {
+                object_id: Cow::Borrowed(&SCRIPT_COMMAND_OBJECT_GUID[..]),
+                object_size,
+                reserved: Cow::Borrowed(reserved),
+                commands_count,
+                command_types_count,
+                command_types,
+                commands,
+            })
+    )
+);
+
+// Command Media のメディアオブジェクトを種類と引数に分ける.
+// データはヌル終端された UTF-16LE の種類と引数が続いたもの
+pub fn parse_command_payload(data: &[u8]) -> Option<(String, String)> {
+    let end = data.chunks(2).position(|c| c == [0, 0])? * 2;
+    Some((utf16::decode(&data[..end]), utf16::decode(&data[end + 2..])))
+}
+
+impl<'a> ASF<'a> {
+    // Script Command Object と Command Media のストリームのコマンドを表示時間順に返す
+    pub fn commands(&self) -> Result<Vec<Command>> {
+        let preroll = self
+            .file_props_object
+            .as_ref()
+            .map(|v| v.preroll_time())
+            .unwrap_or_default();
+        let command = |presentation_time: u32, command_type: String, parameter: String| Command {
+            presentation_time: Duration::from_millis(u64::from(presentation_time))
+                .checked_sub(preroll)
+                .unwrap_or_default(),
+            command_type,
+            parameter,
+        };
+
+        let mut commands = Vec::new();
+        if let Some(ref script_command_object) = self.script_command_object {
+            for v in &script_command_object.commands {
+                let command_type = script_command_object
+                    .command_types
+                    .get(v.type_index as usize)
+                    .map(CommandType::name)
+                    .unwrap_or_default();
+                commands.push(command(v.presentation_time, command_type, v.name()));
+            }
+        }
+
+        let command_streams = self
+            .stream_props_objects
+            .iter()
+            .filter(|v| v.stream_kind() == StreamKind::Command)
+            .map(|v| v.stream_number())
+            .collect::<Vec<_>>();
+        if !command_streams.is_empty() {
+            if let Some(media_objects) = self.media_objects() {
+                for media_object in media_objects {
+                    let media_object = media_object?;
+                    if !command_streams.contains(&media_object.stream_number) {
+                        continue;
+                    }
+                    if let Some((command_type, parameter)) =
+                        parse_command_payload(&media_object.data)
+                    {
+                        commands.push(command(
+                            media_object.presentation_time,
+                            command_type,
+                            parameter,
+                        ));
+                    }
+                }
+            }
+        }
+
+        commands.sort_by_key(|v| v.presentation_time);
+        Ok(commands)
+    }
+}
+
+// CAPTION コマンドの (開始時間, 終了時間, テキスト).
+// 次の CAPTION コマンドまで表示し、空のキャプションは表示を消すだけにする
+fn caption_cues(commands: &[Command], duration: Duration) -> Vec<(Duration, Duration, &str)> {
+    let captions = commands
+        .iter()
+        .filter(|v| v.is_caption())
+        .collect::<Vec<_>>();
+    captions
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| !v.parameter.trim().is_empty())
+        .map(|(i, v)| {
+            let end = match captions.get(i + 1) {
+                Some(next) => next.presentation_time,
+                None => duration.max(v.presentation_time),
+            };
+            (v.presentation_time, end, v.parameter.as_str())
+        })
+        .collect()
+}
+
+// "01:02:03,500" のように表示する
+fn subtitle_timestamp(time: Duration, separator: char) -> String {
+    let secs = time.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        separator,
+        time.subsec_millis()
+    )
+}
+
+// CAPTION コマンドを SRT に変換する
+pub fn to_srt(commands: &[Command], duration: Duration) -> String {
+    let mut s = String::new();
+    for (i, (start, end, text)) in caption_cues(commands, duration).into_iter().enumerate() {
+        let _ = write!(
+            s,
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            subtitle_timestamp(start, ','),
+            subtitle_timestamp(end, ','),
+            text
+        );
+    }
+    s
+}
+
+// CAPTION コマンドを WebVTT に変換する
+pub fn to_webvtt(commands: &[Command], duration: Duration) -> String {
+    let mut s = String::from("WEBVTT\n");
+    for (start, end, text) in caption_cues(commands, duration) {
+        let text = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let _ = write!(
+            s,
+            "\n{} --> {}\n{}\n",
+            subtitle_timestamp(start, '.'),
+            subtitle_timestamp(end, '.'),
+            text
+        );
+    }
+    s
+}
+
+#[test]
+fn parse_script_command_object_test() {
+    let mut input = Vec::new();
+    input.extend_from_slice(&[0; 8]);
+    input.extend_from_slice(&[
+        227, 203, 26, 75, 11, 16, 208, 17, 163, 155, 0, 160, 201, 3, 72, 246,
+    ]);
+    input.extend_from_slice(&[3, 0, 2, 0]);
+    for name in &["URL\0", "CAPTION\0"] {
+        input.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        input.extend_from_slice(&utf16::encode(name));
+    }
+    for (presentation_time, type_index, name) in &[
+        (5100u32, 1u16, "Hello\0"),
+        (3100, 0, "http://example.com/\0"),
+        (8100, 1, "\0"),
+    ] {
+        input.extend_from_slice(&presentation_time.to_le_bytes());
+        input.extend_from_slice(&type_index.to_le_bytes());
+        input.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        input.extend_from_slice(&utf16::encode(name));
+    }
+    let object_size = input.len() as u64 + 16;
+    input[..8].copy_from_slice(&object_size.to_le_bytes());
+    let (remain, script_command_object) = parse_script_command_object(&input).unwrap();
+
+    assert!(remain.is_empty());
+    assert_eq!(script_command_object.command_types[1].name(), "CAPTION");
+    assert_eq!(script_command_object.commands[0].name(), "Hello");
+
+    let input = include_bytes!("../assets/320x180_10fps.asf");
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+    asf.script_command_object = Some(Box::new(script_command_object.into_owned()));
+    let preroll = asf.file_props_object.as_ref().unwrap().preroll_time();
+    let commands = asf.commands().unwrap();
+
+    assert_eq!(
+        commands[0],
+        Command {
+            presentation_time: Duration::from_millis(3100) - preroll,
+            command_type: "URL".to_string(),
+            parameter: "http://example.com/".to_string(),
+        }
+    );
+    assert!(commands[1].is_caption());
+    assert_eq!(
+        to_srt(&commands, Duration::from_secs(10)),
+        format!(
+            "1\n{} --> {}\nHello\n\n",
+            subtitle_timestamp(Duration::from_millis(5100) - preroll, ','),
+            subtitle_timestamp(Duration::from_millis(8100) - preroll, ','),
+        )
+    );
+}
+
+#[test]
+fn captions_export_test() {
+    let caption = |millis: u64, parameter: &str| Command {
+        presentation_time: Duration::from_millis(millis),
+        command_type: "caption".to_string(),
+        parameter: parameter.to_string(),
+    };
+    let commands = vec![
+        caption(1500, "Hello"),
+        caption(3_723_250, "<b>Tom & Jerry</b>"),
+    ];
+
+    assert_eq!(
+        to_srt(&commands, Duration::from_secs(4000)),
+        "1\n00:00:01,500 --> 01:02:03,250\nHello\n\n\
+         2\n01:02:03,250 --> 01:06:40,000\n<b>Tom & Jerry</b>\n\n"
+    );
+    assert_eq!(
+        to_webvtt(&commands, Duration::from_secs(4000)),
+        "WEBVTT\n\
+         \n00:00:01.500 --> 01:02:03.250\nHello\n\
+         \n01:02:03.250 --> 01:06:40.000\n&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;\n"
+    );
+    assert_eq!(
+        parse_command_payload(&utf16::encode("CAPTION\0Hi\0")),
+        Some(("CAPTION".to_string(), "Hi".to_string()))
+    );
+}
@@ -0,0 +1,111 @@
+use std::borrow::Cow;
+
+use nom::{le_u16, le_u8};
+
+// Audio Spread の Error Correction Data
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AudioSpread<'a> {
+    // インターリーブする仮想パケットの数
+    pub span: u8,
+    // 仮想パケットのサイズ
+    pub virtual_packet_length: u16,
+    // 仮想チャンクのサイズ
+    pub virtual_chunk_length: u16,
+    // Silence Dataのサイズ
+    pub silence_data_length: u16,
+    // 欠落したチャンクを埋めるためのデータ
+    pub silence_data: Cow<'a, [u8]>,
+}
+
+impl<'a> AudioSpread<'a> {
+    // インターリーブされたメディアオブジェクトを元の順序に戻す.
+    // メディアオブジェクトのサイズが span * virtual_packet_length と一致しない場合はそのまま返す
+    pub fn descramble(&self, data: &[u8]) -> Vec<u8> {
+        let span = self.span as usize;
+        let packet_length = self.virtual_packet_length as usize;
+        let chunk_length = self.virtual_chunk_length as usize;
+        if span <= 1
+            || chunk_length == 0
+            || !packet_length.is_multiple_of(chunk_length)
+            || data.len() != span * packet_length
+        {
+            return data.to_vec();
+        }
+
+        let chunks_per_packet = packet_length / chunk_length;
+        let mut descrambled = Vec::with_capacity(data.len());
+        for i in 0..data.len() / chunk_length {
+            let row = i / span;
+            let col = i % span;
+            let index = row + col * chunks_per_packet;
+            descrambled.extend_from_slice(&data[index * chunk_length..(index + 1) * chunk_length]);
+        }
+        descrambled
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> AudioSpread<'static> {
+        AudioSpread {
+            span: self.span,
+            virtual_packet_length: self.virtual_packet_length,
+            virtual_chunk_length: self.virtual_chunk_length,
+            silence_data_length: self.silence_data_length,
+            silence_data: Cow::Owned(self.silence_data.into_owned()),
+        }
+    }
+}
+
+named!(
+    pub parse_audio_spread<AudioSpread>,
+    do_parse!(
+        span: le_u8
+            >> virtual_packet_length: le_u16
+            >> virtual_chunk_length: le_u16
+            >> silence_data_length: le_u16
+            >> silence_data: take!(silence_data_length)
+            >> (AudioSpread {
+                span,
+                virtual_packet_length,
+                virtual_chunk_length,
+                silence_data_length,
+                silence_data: Cow::Borrowed(silence_data),
+            })
+    )
+);
+
+#[test]
+fn audio_spread_test1() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let audio_spread = asf.stream_props_objects[0].audio_spread().unwrap();
+
+    assert_eq!(
+        audio_spread,
+        AudioSpread {
+            span: 1,
+            virtual_packet_length: 1487,
+            virtual_chunk_length: 1487,
+            silence_data_length: 1,
+            silence_data: Cow::Owned(vec![0]),
+        }
+    );
+    assert_eq!(asf.stream_props_objects[1].audio_spread(), None);
+}
+
+#[test]
+fn audio_spread_descramble_test() {
+    let audio_spread = AudioSpread {
+        span: 2,
+        virtual_packet_length: 4,
+        virtual_chunk_length: 2,
+        silence_data_length: 0,
+        silence_data: Cow::Borrowed(&[]),
+    };
+
+    assert_eq!(
+        audio_spread.descramble(&[0, 1, 2, 3, 4, 5, 6, 7]),
+        vec![0, 1, 4, 5, 2, 3, 6, 7]
+    );
+    // サイズが一致しない場合はそのまま
+    assert_eq!(audio_spread.descramble(&[0, 1, 2, 3]), vec![0, 1, 2, 3]);
+}
@@ -0,0 +1,517 @@
+use std::borrow::Cow;
+
+use nom::{le_u16, le_u32, le_u64, rest, IResult};
+
+use crate::audio::{parse_audio_format, AudioFormat};
+use crate::utf16;
+use crate::video::{parse_video_format, VideoFormat};
+use crate::{
+    parse_stream_props_object, StreamPropertiesObject, EXTENDED_STREAM_PROPERTIES_OBJECT_GUID,
+    STREAM_PROPERTIES_OBJECT_GUID, STREAM_PROPERTIES_OBJECT_STREAM_TYPE_AUDIO_GUID,
+    STREAM_PROPERTIES_OBJECT_STREAM_TYPE_BINARY_GUID,
+    STREAM_PROPERTIES_OBJECT_STREAM_TYPE_COMMAND_GUID,
+    STREAM_PROPERTIES_OBJECT_STREAM_TYPE_DEGRADABLE_JPEG_GUID,
+    STREAM_PROPERTIES_OBJECT_STREAM_TYPE_FILE_TRANSFER_GUID,
+    STREAM_PROPERTIES_OBJECT_STREAM_TYPE_JFIF_GUID,
+    STREAM_PROPERTIES_OBJECT_STREAM_TYPE_VIDEO_GUID, WEB_STREAM_FORMAT_GUID,
+    WEB_STREAM_MEDIA_SUBTYPE_GUID,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StreamKind {
+    Audio,
+    Video,
+    // スクリプトコマンド
+    Command,
+    Jfif,
+    DegradableJpeg,
+    FileTransfer,
+    Binary,
+    // Binary のうち Media Subtype が Web Stream のもの
+    WebStream,
+    Unknown,
+}
+
+impl StreamKind {
+    // Stream Properties Object の Stream Type から判定する. WebStream は Binary になる
+    pub fn from_guid(guid: &[u8]) -> StreamKind {
+        if guid == STREAM_PROPERTIES_OBJECT_STREAM_TYPE_AUDIO_GUID {
+            StreamKind::Audio
+        } else if guid == STREAM_PROPERTIES_OBJECT_STREAM_TYPE_VIDEO_GUID {
+            StreamKind::Video
+        } else if guid == STREAM_PROPERTIES_OBJECT_STREAM_TYPE_COMMAND_GUID {
+            StreamKind::Command
+        } else if guid == STREAM_PROPERTIES_OBJECT_STREAM_TYPE_JFIF_GUID {
+            StreamKind::Jfif
+        } else if guid == STREAM_PROPERTIES_OBJECT_STREAM_TYPE_DEGRADABLE_JPEG_GUID {
+            StreamKind::DegradableJpeg
+        } else if guid == STREAM_PROPERTIES_OBJECT_STREAM_TYPE_FILE_TRANSFER_GUID {
+            StreamKind::FileTransfer
+        } else if guid == STREAM_PROPERTIES_OBJECT_STREAM_TYPE_BINARY_GUID {
+            StreamKind::Binary
+        } else {
+            StreamKind::Unknown
+        }
+    }
+}
+
+// JFIF Media Type
+#[derive(Debug, PartialEq, Eq)]
+pub struct JfifFormat {
+    // 幅
+    pub image_width: u32,
+    // 高さ
+    pub image_height: u32,
+    // 予約領域
+    pub reserved: u32,
+}
+
+// Degradable JPEG Media Type
+#[derive(Debug, PartialEq, Eq)]
+pub struct DegradableJpegFormat<'a> {
+    // 幅
+    pub image_width: u32,
+    // 高さ
+    pub image_height: u32,
+    // 予約領域
+    pub reserved_1: u16,
+    // 予約領域
+    pub reserved_2: u16,
+    // 予約領域
+    pub reserved_3: u16,
+    // Interchange Dataのサイズ
+    pub interchange_data_length: u16,
+    // JPEG の量子化テーブルなど
+    pub interchange_data: Cow<'a, [u8]>,
+}
+
+// Web Stream Format の Format Data
+#[derive(Debug, PartialEq, Eq)]
+pub struct WebStreamFormat {
+    // Web Stream Format Data 全体のサイズ
+    pub web_stream_format_data_size: u16,
+    // サンプル先頭の固定長ヘッダーのサイズ
+    pub fixed_sample_header_size: u16,
+    // バージョン. 常に1
+    pub version_number: u16,
+    // 予約領域
+    pub reserved: u16,
+}
+
+// File Transfer Media Type, Binary Media Type
+#[derive(Debug, PartialEq, Eq)]
+pub struct BinaryFormat<'a> {
+    // GUID
+    pub major_media_type: Cow<'a, [u8]>,
+    // GUID. Web Stream の場合は WEB_STREAM_MEDIA_SUBTYPE_GUID
+    pub media_subtype: Cow<'a, [u8]>,
+    // サンプルが固定長かどうか
+    pub fixed_size_samples: u32,
+    // 時間方向の圧縮をしているかどうか
+    pub temporal_compression: u32,
+    // 固定長の場合のサンプルのサイズ
+    pub sample_size: u32,
+    // GUID. Web Stream の場合は WEB_STREAM_FORMAT_GUID
+    pub format_type: Cow<'a, [u8]>,
+    // Format Dataのサイズ
+    pub format_data_size: u32,
+    pub format_data: Cow<'a, [u8]>,
+    // format_type が WEB_STREAM_FORMAT_GUID の場合に format_data を解析したもの
+    pub web_stream: Option<WebStreamFormat>,
+}
+
+impl<'a> BinaryFormat<'a> {
+    pub fn is_web_stream(&self) -> bool {
+        *self.media_subtype == WEB_STREAM_MEDIA_SUBTYPE_GUID
+    }
+}
+
+// ストリームの種類ごとに解析した Type-Specific Data
+#[derive(Debug, PartialEq, Eq)]
+pub enum TypeSpecificData<'a> {
+    Audio(AudioFormat<'a>),
+    Video(VideoFormat<'a>),
+    Jfif(JfifFormat),
+    DegradableJpeg(DegradableJpegFormat<'a>),
+    // File Transfer, Binary, Web Stream
+    Binary(BinaryFormat<'a>),
+}
+
+named!(
+    pub parse_jfif_format<JfifFormat>,
+    do_parse!(
+        image_width: le_u32
+            >> image_height: le_u32
+            >> reserved: le_u32
+            >> (JfifFormat {
+                image_width,
+                image_height,
+                reserved,
+            })
+    )
+);
+
+named!(
+    pub parse_degradable_jpeg_format<DegradableJpegFormat>,
+    do_parse!(
+        image_width: le_u32
+            >> image_height: le_u32
+            >> reserved_1: le_u16
+            >> reserved_2: le_u16
+            >> reserved_3: le_u16
+            >> interchange_data_length: le_u16
+            >> interchange_data: take!(interchange_data_length)
+            >> (DegradableJpegFormat {
+                image_width,
+                image_height,
+                reserved_1,
+                reserved_2,
+                reserved_3,
+                interchange_data_length,
+                interchange_data: Cow::Borrowed(interchange_data),
+            })
+    )
+);
+
+named!(
+    parse_web_stream_format<WebStreamFormat>,
+    do_parse!(
+        web_stream_format_data_size: le_u16
+            >> fixed_sample_header_size: le_u16
+            >> version_number: le_u16
+            >> reserved: le_u16
+            >> (WebStreamFormat {
+                web_stream_format_data_size,
+                fixed_sample_header_size,
+                version_number,
+                reserved,
+            })
+    )
+);
+
+named!(
+    pub parse_binary_format<BinaryFormat>,
+    do_parse!(
+        major_media_type: take!(16)
+            >> media_subtype: take!(16)
+            >> fixed_size_samples: le_u32
+            >> temporal_compression: le_u32
+            >> sample_size: le_u32
+            >> format_type: take!(16)
+            >> format_data_size: le_u32
+            >> format_data: take!(format_data_size)
+            >> (BinaryFormat {
+                major_media_type: Cow::Borrowed(major_media_type),
+                media_subtype: Cow::Borrowed(media_subtype),
+                fixed_size_samples,
+                temporal_compression,
+                sample_size,
+                format_type: Cow::Borrowed(format_type),
+                format_data_size,
+                format_data: Cow::Borrowed(format_data),
+                web_stream: if format_type == WEB_STREAM_FORMAT_GUID {
+                    parse_web_stream_format(format_data).ok().map(|(_, v)| v)
+                } else {
+                    None
+                },
+            })
+    )
+);
+
+// stream_kind に従って Type-Specific Data を解析する. Type-Specific Data を持たない種類の場合は None
+pub fn parse_type_specific_data(
+    input: &[u8],
+    stream_kind: StreamKind,
+) -> IResult<&[u8], Option<TypeSpecificData<'_>>> {
+    let (input, data) = match stream_kind {
+        StreamKind::Audio => {
+            let (input, v) = parse_audio_format(input)?;
+            (input, TypeSpecificData::Audio(v))
+        }
+        StreamKind::Video => {
+            let (input, v) = parse_video_format(input)?;
+            (input, TypeSpecificData::Video(v))
+        }
+        StreamKind::Jfif => {
+            let (input, v) = parse_jfif_format(input)?;
+            (input, TypeSpecificData::Jfif(v))
+        }
+        StreamKind::DegradableJpeg => {
+            let (input, v) = parse_degradable_jpeg_format(input)?;
+            (input, TypeSpecificData::DegradableJpeg(v))
+        }
+        StreamKind::FileTransfer | StreamKind::Binary | StreamKind::WebStream => {
+            let (input, v) = parse_binary_format(input)?;
+            (input, TypeSpecificData::Binary(v))
+        }
+        StreamKind::Command | StreamKind::Unknown => return Ok((input, None)),
+    };
+    Ok((input, Some(data)))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct StreamName<'a> {
+    // Language List Object のインデックス
+    pub language_id_index: u16,
+    // Stream Name のバイト数
+    pub stream_name_length: u16,
+    // UTF-16LE
+    pub stream_name: Cow<'a, [u8]>,
+}
+
+impl<'a> StreamName<'a> {
+    pub fn name(&self) -> String {
+        utf16::decode(&self.stream_name)
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> StreamName<'static> {
+        StreamName {
+            language_id_index: self.language_id_index,
+            stream_name_length: self.stream_name_length,
+            stream_name: Cow::Owned(self.stream_name.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PayloadExtensionSystem<'a> {
+    // GUID
+    pub extension_system_id: Cow<'a, [u8]>,
+    // ペイロードごとの拡張データのサイズ. 可変長の場合は 0xFFFF
+    pub extension_data_size: u16,
+    // Extension System Info のサイズ
+    pub extension_system_info_length: u32,
+    pub extension_system_info: Cow<'a, [u8]>,
+}
+
+impl<'a> PayloadExtensionSystem<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> PayloadExtensionSystem<'static> {
+        PayloadExtensionSystem {
+            extension_system_id: Cow::Owned(self.extension_system_id.into_owned()),
+            extension_data_size: self.extension_data_size,
+            extension_system_info_length: self.extension_system_info_length,
+            extension_system_info: Cow::Owned(self.extension_system_info.into_owned()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExtendedStreamPropertiesObject<'a> {
+    // 14E6A5CB-C672-4332-8399-A96952065B5A
+    pub object_id: Cow<'a, [u8]>,
+    // オブジェクト全体のサイズ
+    pub object_size: u64,
+    // ストリームの開始時間 (ミリ秒)
+    pub start_time: u64,
+    // ストリームの終了時間 (ミリ秒)
+    pub end_time: u64,
+    // リーキーバケットの平均ビットレート (bits/sec)
+    pub data_bitrate: u32,
+    // リーキーバケットのサイズ (ミリ秒)
+    pub buffer_size: u32,
+    // 初期のバッファ量 (ミリ秒)
+    pub initial_buffer_fullness: u32,
+    // ピーク時のビットレート (bits/sec)
+    pub alternate_data_bitrate: u32,
+    // ピーク時のバッファのサイズ (ミリ秒)
+    pub alternate_buffer_size: u32,
+    // ピーク時の初期のバッファ量 (ミリ秒)
+    pub alternate_initial_buffer_fullness: u32,
+    // メディアオブジェクトの最大サイズ
+    pub maximum_object_size: u32,
+    // Reliable(0x01), Seekable(0x02), No Cleanpoints(0x04), Resend Live Cleanpoints(0x08)
+    pub flags: u32,
+    // ストリーム番号
+    pub stream_number: u16,
+    // Language List Object のインデックス
+    pub stream_language_id_index: u16,
+    // 1フレームあたりの時間 (100ナノ秒)
+    pub average_time_per_frame: u64,
+    // Stream Names の総数
+    pub stream_name_count: u16,
+    // Payload Extension Systems の総数
+    pub payload_extension_system_count: u16,
+    pub stream_names: Vec<StreamName<'a>>,
+    pub payload_extension_systems: Vec<PayloadExtensionSystem<'a>>,
+    // Header Object に Stream Properties Object が無いストリームの場合に含まれる
+    pub stream_props_object: Option<Box<StreamPropertiesObject<'a>>>,
+}
+
+impl<'a> ExtendedStreamPropertiesObject<'a> {
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> ExtendedStreamPropertiesObject<'static> {
+        ExtendedStreamPropertiesObject {
+            object_id: Cow::Owned(self.object_id.into_owned()),
+            object_size: self.object_size,
+            start_time: self.start_time,
+            end_time: self.end_time,
+            data_bitrate: self.data_bitrate,
+            buffer_size: self.buffer_size,
+            initial_buffer_fullness: self.initial_buffer_fullness,
+            alternate_data_bitrate: self.alternate_data_bitrate,
+            alternate_buffer_size: self.alternate_buffer_size,
+            alternate_initial_buffer_fullness: self.alternate_initial_buffer_fullness,
+            maximum_object_size: self.maximum_object_size,
+            flags: self.flags,
+            stream_number: self.stream_number,
+            stream_language_id_index: self.stream_language_id_index,
+            average_time_per_frame: self.average_time_per_frame,
+            stream_name_count: self.stream_name_count,
+            payload_extension_system_count: self.payload_extension_system_count,
+            stream_names: self
+                .stream_names
+                .into_iter()
+                .map(StreamName::into_owned)
+                .collect(),
+            payload_extension_systems: self
+                .payload_extension_systems
+                .into_iter()
+                .map(PayloadExtensionSystem::into_owned)
+                .collect(),
+            stream_props_object: self.stream_props_object.map(|v| Box::new(v.into_owned())),
+        }
+    }
+}
+
+named!(
+    parse_stream_name<StreamName>,
+    do_parse!(
+        language_id_index: le_u16
+            >> stream_name_length: le_u16
+            >> stream_name: take!(stream_name_length)
+            >> (StreamName {
+                language_id_index,
+                stream_name_length,
+                stream_name: Cow::Borrowed(stream_name),
+            })
+    )
+);
+
+named!(
+    parse_payload_extension_system<PayloadExtensionSystem>,
+    do_parse!(
+        extension_system_id: take!(16)
+            >> extension_data_size: le_u16
+            >> extension_system_info_length: le_u32
+            >> extension_system_info: take!(extension_system_info_length)
+            >> (PayloadExtensionSystem {
+                extension_system_id: Cow::Borrowed(extension_system_id),
+                extension_data_size,
+                extension_system_info_length,
+                extension_system_info: Cow::Borrowed(extension_system_info),
+            })
+    )
+);
+
+// 残りのデータが Stream Properties Object であれば解析する
+fn parse_embedded_stream_props_object(input: &[u8]) -> Option<Box<StreamPropertiesObject<'_>>> {
+    if input.len() < 16 || input[..16] != STREAM_PROPERTIES_OBJECT_GUID {
+        return None;
+    }
+    parse_stream_props_object(&input[16..])
+        .ok()
+        .map(|(_, v)| Box::new(v))
+}
+
+named!(
+    pub parse_extended_stream_props_object<ExtendedStreamPropertiesObject>,
+    do_parse!(
+        object_size: le_u64
+            >> start_time: le_u64
+            >> end_time: le_u64
+            >> data_bitrate: le_u32
+            >> buffer_size: le_u32
+            >> initial_buffer_fullness: le_u32
+            >> alternate_data_bitrate: le_u32
+            >> alternate_buffer_size: le_u32
+            >> alternate_initial_buffer_fullness: le_u32
+            >> maximum_object_size: le_u32
+            >> flags: le_u32
+            >> stream_number: le_u16
+            >> stream_language_id_index: le_u16
+            >> average_time_per_frame: le_u64
+            >> stream_name_count: le_u16
+            >> payload_extension_system_count: le_u16
+            >> stream_names:
+                many_m_n!(
+                    stream_name_count as usize,
+                    stream_name_count as usize,
+                    parse_stream_name
+                )
+            >> payload_extension_systems:
+                many_m_n!(
+                    payload_extension_system_count as usize,
+                    payload_extension_system_count as usize,
+                    parse_payload_extension_system
+                )
+            >> stream_props_object: rest
+            >> (ExtendedStreamPropertiesObject {
+                object_id: Cow::Borrowed(&EXTENDED_STREAM_PROPERTIES_OBJECT_GUID[..]),
+                object_size,
+                start_time,
+                end_time,
+                data_bitrate,
+                buffer_size,
+                initial_buffer_fullness,
+                alternate_data_bitrate,
+                alternate_buffer_size,
+                alternate_initial_buffer_fullness,
+                maximum_object_size,
+                flags,
+                stream_number,
+                stream_language_id_index,
+                average_time_per_frame,
+                stream_name_count,
+                payload_extension_system_count,
+                stream_names,
+                payload_extension_systems,
+                stream_props_object: parse_embedded_stream_props_object(stream_props_object),
+            })
+    )
+);
+
+#[test]
+fn stream_kind_test1() {
+    let input = include_bytes!("../assets/320x180_10fps.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+
+    let kinds = asf
+        .stream_props_objects
+        .iter()
+        .map(|v| v.stream_kind())
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, vec![StreamKind::Video, StreamKind::Audio]);
+    match asf.stream_props_objects[1].type_specific() {
+        Some(TypeSpecificData::Audio(audio_format)) => assert_eq!(audio_format.channels, 1),
+        v => panic!("unexpected type specific data: {:?}", v),
+    }
+}
+
+#[test]
+fn parse_type_specific_data_web_stream_test() {
+    let mut input = Vec::new();
+    input.extend_from_slice(&STREAM_PROPERTIES_OBJECT_STREAM_TYPE_BINARY_GUID);
+    input.extend_from_slice(&WEB_STREAM_MEDIA_SUBTYPE_GUID);
+    input.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    input.extend_from_slice(&WEB_STREAM_FORMAT_GUID);
+    input.extend_from_slice(&[8, 0, 0, 0, 8, 0, 10, 0, 1, 0, 0, 0]);
+    let (_, data) = parse_type_specific_data(&input, StreamKind::Binary).unwrap();
+
+    match data {
+        Some(TypeSpecificData::Binary(binary_format)) => {
+            assert!(binary_format.is_web_stream());
+            assert_eq!(
+                binary_format.web_stream,
+                Some(WebStreamFormat {
+                    web_stream_format_data_size: 8,
+                    fixed_sample_header_size: 10,
+                    version_number: 1,
+                    reserved: 0,
+                })
+            );
+        }
+        v => panic!("unexpected type specific data: {:?}", v),
+    }
+}
@@ -0,0 +1,18 @@
+// UTF-16LE のバイト列を文字列に変換する. 末尾のヌル文字は取り除く
+pub(crate) fn decode(bytes: &[u8]) -> String {
+    let units = bytes
+        .chunks(2)
+        .filter(|c| c.len() == 2)
+        .map(|c| u16::from(c[0]) | u16::from(c[1]) << 8)
+        .collect::<Vec<_>>();
+    let s = String::from_utf16_lossy(&units);
+    s.trim_end_matches('\0').to_string()
+}
+
+// 文字列を UTF-16LE のバイト列に変換する. ヌル文字は付加しない
+#[cfg(test)]
+pub(crate) fn encode(s: &str) -> Vec<u8> {
+    s.encode_utf16()
+        .flat_map(|v| v.to_le_bytes().to_vec())
+        .collect()
+}
@@ -0,0 +1,196 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use nom::{le_i32, le_u16, le_u32, le_u8, rest, IResult};
+
+// BITMAPINFOHEADER
+#[derive(Debug, PartialEq, Eq)]
+pub struct BitmapInfoHeader<'a> {
+    // BITMAPINFOHEADER 全体のサイズ (biSize)
+    pub format_data_size: u32,
+    // 幅
+    pub image_width: i32,
+    // 高さ
+    pub image_height: i32,
+    // 予約領域. 常に1
+    pub reserved: u16,
+    // 1ピクセルあたりのビット数
+    pub bits_per_pixel_count: u16,
+    // コーデックの FourCC. WMV2, WMV3, WVC1, ...
+    pub compression_id: [u8; 4],
+    // 画像のサイズ
+    pub image_size: u32,
+    // 水平方向の解像度
+    pub horizontal_pixels_per_meter: i32,
+    // 垂直方向の解像度
+    pub vertical_pixels_per_meter: i32,
+    // カラーテーブルの色数
+    pub colors_used_count: u32,
+    // 重要な色数
+    pub important_colors_count: u32,
+    // コーデック固有のデータ
+    pub codec_specific_data: Cow<'a, [u8]>,
+}
+
+// Video Media Type
+#[derive(Debug, PartialEq, Eq)]
+pub struct VideoFormat<'a> {
+    // エンコードされた画像の幅
+    pub encoded_image_width: u32,
+    // エンコードされた画像の高さ
+    pub encoded_image_height: u32,
+    // 予約領域. 常に2
+    pub reserved_flags: u8,
+    // Format Data のサイズ
+    pub format_data_size: u16,
+    pub format_data: BitmapInfoHeader<'a>,
+}
+
+impl<'a> VideoFormat<'a> {
+    // コーデックの FourCC
+    pub fn fourcc(&self) -> String {
+        String::from_utf8_lossy(&self.format_data.compression_id).into_owned()
+    }
+
+    // 入力バッファから切り離し、'static なデータに変換する
+    pub fn into_owned(self) -> VideoFormat<'static> {
+        let format_data = self.format_data;
+        VideoFormat {
+            encoded_image_width: self.encoded_image_width,
+            encoded_image_height: self.encoded_image_height,
+            reserved_flags: self.reserved_flags,
+            format_data_size: self.format_data_size,
+            format_data: BitmapInfoHeader {
+                format_data_size: format_data.format_data_size,
+                image_width: format_data.image_width,
+                image_height: format_data.image_height,
+                reserved: format_data.reserved,
+                bits_per_pixel_count: format_data.bits_per_pixel_count,
+                compression_id: format_data.compression_id,
+                image_size: format_data.image_size,
+                horizontal_pixels_per_meter: format_data.horizontal_pixels_per_meter,
+                vertical_pixels_per_meter: format_data.vertical_pixels_per_meter,
+                colors_used_count: format_data.colors_used_count,
+                important_colors_count: format_data.important_colors_count,
+                codec_specific_data: Cow::Owned(format_data.codec_specific_data.into_owned()),
+            },
+        }
+    }
+}
+
+// "WMV3 640x360" のように表示する
+impl<'a> fmt::Display for VideoFormat<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}x{}",
+            self.fourcc(),
+            self.encoded_image_width,
+            self.encoded_image_height
+        )
+    }
+}
+
+named!(
+    parse_bitmap_info_header<BitmapInfoHeader>,
+    do_parse!(
+        format_data_size: le_u32
+            >> image_width: le_i32
+            >> image_height: le_i32
+            >> reserved: le_u16
+            >> bits_per_pixel_count: le_u16
+            >> compression_id: take!(4)
+            >> image_size: le_u32
+            >> horizontal_pixels_per_meter: le_i32
+            >> vertical_pixels_per_meter: le_i32
+            >> colors_used_count: le_u32
+            >> important_colors_count: le_u32
+            >> codec_specific_data: rest
+            >> (BitmapInfoHeader {
+                format_data_size,
+                image_width,
+                image_height,
+                reserved,
+                bits_per_pixel_count,
+                compression_id: [
+                    compression_id[0],
+                    compression_id[1],
+                    compression_id[2],
+                    compression_id[3],
+                ],
+                image_size,
+                horizontal_pixels_per_meter,
+                vertical_pixels_per_meter,
+                colors_used_count,
+                important_colors_count,
+                codec_specific_data: Cow::Borrowed(codec_specific_data),
+            })
+    )
+);
+
+// Video Media Type の Type-Specific Data を解析する
+pub fn parse_video_format(input: &[u8]) -> IResult<&[u8], VideoFormat<'_>> {
+    do_parse!(
+        input,
+        encoded_image_width: le_u32
+            >> encoded_image_height: le_u32
+            >> reserved_flags: le_u8
+            >> format_data_size: le_u16
+            >> format_data: flat_map!(take!(format_data_size), parse_bitmap_info_header)
+            >> (VideoFormat {
+                encoded_image_width,
+                encoded_image_height,
+                reserved_flags,
+                format_data_size,
+                format_data,
+            })
+    )
+}
+
+#[test]
+fn parse_video_format_test1() {
+    let input = include_bytes!("../assets/320x180_10fps.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let video_format = asf.stream_props_objects[0].video_format().unwrap();
+
+    assert_eq!(
+        video_format,
+        VideoFormat {
+            encoded_image_width: 320,
+            encoded_image_height: 180,
+            reserved_flags: 2,
+            format_data_size: 44,
+            format_data: BitmapInfoHeader {
+                format_data_size: 44,
+                image_width: 320,
+                image_height: 180,
+                reserved: 1,
+                bits_per_pixel_count: 24,
+                compression_id: *b"WMV2",
+                image_size: 172800,
+                horizontal_pixels_per_meter: 0,
+                vertical_pixels_per_meter: 0,
+                colors_used_count: 0,
+                important_colors_count: 0,
+                codec_specific_data: Cow::Owned(vec![80, 195, 180, 128]),
+            },
+        }
+    );
+    assert_eq!(video_format.to_string(), "WMV2 320x180");
+    assert_eq!(asf.stream_props_objects[1].video_format(), None);
+}
+
+#[test]
+fn parse_video_format_test2() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let video_format = asf.stream_props_objects[1].video_format().unwrap();
+
+    assert_eq!(video_format.fourcc(), "WMV3");
+    assert_eq!(video_format.format_data.image_width, 640);
+    assert_eq!(video_format.format_data.image_height, 360);
+    assert_eq!(
+        video_format.format_data.codec_specific_data,
+        Cow::Borrowed(&[78, 137, 72, 1, 32][..])
+    );
+}
@@ -0,0 +1,1212 @@
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Write};
+
+use crate::audio::AudioFormat;
+use crate::branding::ContentBrandingObject;
+use crate::codec::{CodecEntry, CodecListObject};
+use crate::compatibility::CompatibilityObject;
+use crate::correction::ErrorCorrectionObject;
+use crate::encryption::{
+    ContentEncryptionObject, DigitalSignatureObject, ExtendedContentEncryptionObject,
+};
+use crate::exclusion::{
+    GroupMutualExclusionObject, GroupMutualExclusionRecord, MutualExclusionObject,
+};
+use crate::extension::HeaderExtensionObject;
+use crate::language::{LanguageIdRecord, LanguageListObject};
+use crate::marker::{MarkerEntry, MarkerObject};
+use crate::metadata::{MetadataLibraryObject, MetadataLibraryRecord};
+use crate::packet::{DataPacket, Payload};
+use crate::priority::{BandwidthSharingObject, StreamPrioritizationObject};
+use crate::raw::RawObject;
+use crate::script::{CommandType, ScriptCommand, ScriptCommandObject};
+use crate::stream::{ExtendedStreamPropertiesObject, PayloadExtensionSystem, StreamName};
+use crate::video::{BitmapInfoHeader, VideoFormat};
+use crate::{
+    BitrateRecord, DataObject, FilePropertiesObject, HeaderObject, IndexEntry, SimpleIndexObject,
+    StreamBitratePropertiesObject, StreamPropertiesObject, ASF,
+};
+
+// GUID と Object Size のサイズ
+const OBJECT_HEADER_SIZE: u64 = 24;
+
+// オブジェクトやレコードをバイト列として書き込む.
+// サイズや総数のフィールドは保持している値ではなく、書き込む内容から計算する
+pub trait WriteObject {
+    // 書き込むバイト数
+    fn encoded_size(&self) -> u64;
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()>;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.encoded_size() as usize);
+        self.write_to(&mut bytes)
+            .expect("writing to Vec<u8> never fails");
+        bytes
+    }
+}
+
+// 数やサイズを書き込むフィールドの型に変換する. 収まらない場合は InvalidInput
+fn to_field<T: TryFrom<U>, U: Copy + fmt::Display>(value: U) -> io::Result<T> {
+    T::try_from(value).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} does not fit in the field", value),
+        )
+    })
+}
+
+fn write_object_header<W: Write>(w: &mut W, object_id: &[u8], object_size: u64) -> io::Result<()> {
+    w.write_all(object_id)?;
+    w.write_all(&object_size.to_le_bytes())
+}
+
+fn encoded_size_of<'b, T: WriteObject + 'b, I: IntoIterator<Item = &'b T>>(items: I) -> u64 {
+    items.into_iter().map(WriteObject::encoded_size).sum()
+}
+
+fn write_all_to<'b, T: WriteObject + 'b, I: IntoIterator<Item = &'b T>, W: Write>(
+    w: &mut W,
+    items: I,
+) -> io::Result<()> {
+    for item in items {
+        item.write_to(w)?;
+    }
+    Ok(())
+}
+
+// order に記録された GUID の順に guids のインデックスを並べる.
+// 同じ GUID が複数ある場合は先頭から順に対応させ、order に無いものは末尾に元の順序で追加する
+fn arrange(order: &[Cow<'_, [u8]>], guids: &[&[u8]]) -> Vec<usize> {
+    let mut used = vec![false; guids.len()];
+    let mut indices = Vec::with_capacity(guids.len());
+    for guid in order {
+        if let Some(i) = (0..guids.len()).find(|&i| !used[i] && guids[i] == &guid[..]) {
+            used[i] = true;
+            indices.push(i);
+        }
+    }
+    indices.extend((0..guids.len()).filter(|&i| !used[i]));
+    indices
+}
+
+// GUID とオブジェクトの組を order の順に並べる
+fn arrange_objects<T>(order: &[Cow<'_, [u8]>], objects: Vec<(&[u8], T)>) -> Vec<T> {
+    let guids = objects.iter().map(|v| v.0).collect::<Vec<_>>();
+    let indices = arrange(order, &guids);
+    let mut objects = objects.into_iter().map(|v| Some(v.1)).collect::<Vec<_>>();
+    indices
+        .into_iter()
+        .filter_map(|i| objects[i].take())
+        .collect()
+}
+
+// Object Size, Number of Header Objects は ASF::write_to で子オブジェクトから計算する
+impl<'a> WriteObject for HeaderObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 6
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.object_size)?;
+        w.write_all(&self.num_header_objects.to_le_bytes())?;
+        w.write_all(&[self.reserved_1, self.reserved_2])
+    }
+}
+
+impl<'a> WriteObject for FilePropertiesObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 80
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.file_id)?;
+        w.write_all(&self.file_size.to_le_bytes())?;
+        w.write_all(&self.creation_date.to_le_bytes())?;
+        w.write_all(&self.data_packets_count.to_le_bytes())?;
+        w.write_all(&self.play_duration.to_le_bytes())?;
+        w.write_all(&self.send_duration.to_le_bytes())?;
+        w.write_all(&self.preoll.to_le_bytes())?;
+        w.write_all(&self.flags.to_le_bytes())?;
+        w.write_all(&self.min_data_packet_size.to_le_bytes())?;
+        w.write_all(&self.max_data_packet_size.to_le_bytes())?;
+        w.write_all(&self.max_bitrate.to_le_bytes())
+    }
+}
+
+impl<'a> WriteObject for StreamPropertiesObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE
+            + 54
+            + self.type_specific_data.len() as u64
+            + self.error_correction_data.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.stream_type)?;
+        w.write_all(&self.error_correction_type)?;
+        w.write_all(&self.time_offset.to_le_bytes())?;
+        w.write_all(&to_field::<u32, _>(self.type_specific_data.len())?.to_le_bytes())?;
+        w.write_all(&to_field::<u32, _>(self.error_correction_data.len())?.to_le_bytes())?;
+        w.write_all(&self.flags.to_le_bytes())?;
+        w.write_all(&self.reserved.to_le_bytes())?;
+        w.write_all(&self.type_specific_data)?;
+        w.write_all(&self.error_correction_data)
+    }
+}
+
+// WAVEFORMATEX. Codec Specific Data のサイズ (cbSize) は内容から計算する
+impl<'a> WriteObject for AudioFormat<'a> {
+    fn encoded_size(&self) -> u64 {
+        18 + self.codec_specific_data.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.format_tag.to_le_bytes())?;
+        w.write_all(&self.channels.to_le_bytes())?;
+        w.write_all(&self.samples_per_sec.to_le_bytes())?;
+        w.write_all(&self.avg_bytes_per_sec.to_le_bytes())?;
+        w.write_all(&self.block_align.to_le_bytes())?;
+        w.write_all(&self.bits_per_sample.to_le_bytes())?;
+        w.write_all(&to_field::<u16, _>(self.codec_specific_data.len())?.to_le_bytes())?;
+        w.write_all(&self.codec_specific_data)
+    }
+}
+
+impl<'a> WriteObject for BitmapInfoHeader<'a> {
+    fn encoded_size(&self) -> u64 {
+        40 + self.codec_specific_data.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&to_field::<u32, _>(self.encoded_size())?.to_le_bytes())?;
+        w.write_all(&self.image_width.to_le_bytes())?;
+        w.write_all(&self.image_height.to_le_bytes())?;
+        w.write_all(&self.reserved.to_le_bytes())?;
+        w.write_all(&self.bits_per_pixel_count.to_le_bytes())?;
+        w.write_all(&self.compression_id)?;
+        w.write_all(&self.image_size.to_le_bytes())?;
+        w.write_all(&self.horizontal_pixels_per_meter.to_le_bytes())?;
+        w.write_all(&self.vertical_pixels_per_meter.to_le_bytes())?;
+        w.write_all(&self.colors_used_count.to_le_bytes())?;
+        w.write_all(&self.important_colors_count.to_le_bytes())?;
+        w.write_all(&self.codec_specific_data)
+    }
+}
+
+// Video Media Type の Type-Specific Data
+impl<'a> WriteObject for VideoFormat<'a> {
+    fn encoded_size(&self) -> u64 {
+        11 + self.format_data.encoded_size()
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.encoded_image_width.to_le_bytes())?;
+        w.write_all(&self.encoded_image_height.to_le_bytes())?;
+        w.write_all(&[self.reserved_flags])?;
+        w.write_all(&to_field::<u16, _>(self.format_data.encoded_size())?.to_le_bytes())?;
+        self.format_data.write_to(w)
+    }
+}
+
+impl WriteObject for BitrateRecord {
+    fn encoded_size(&self) -> u64 {
+        6
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.flags.to_le_bytes())?;
+        w.write_all(&self.average_bitrate.to_le_bytes())
+    }
+}
+
+impl<'a> WriteObject for StreamBitratePropertiesObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 2 + encoded_size_of(&self.bitrate_records)
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&to_field::<u16, _>(self.bitrate_records.len())?.to_le_bytes())?;
+        write_all_to(w, &self.bitrate_records)
+    }
+}
+
+impl<'a> WriteObject for DataObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 26 + self.data_packets.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.file_id)?;
+        w.write_all(&self.total_data_packets.to_le_bytes())?;
+        w.write_all(&self.reserved.to_le_bytes())?;
+        w.write_all(&self.data_packets)
+    }
+}
+
+impl WriteObject for IndexEntry {
+    fn encoded_size(&self) -> u64 {
+        6
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.packet_number.to_le_bytes())?;
+        w.write_all(&self.packet_count.to_le_bytes())
+    }
+}
+
+impl<'a> WriteObject for SimpleIndexObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 32 + encoded_size_of(&self.index_entries)
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let maximum_packet_count = self
+            .index_entries
+            .iter()
+            .map(|v| u32::from(v.packet_count))
+            .max()
+            .unwrap_or(0);
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.file_id)?;
+        w.write_all(&self.index_entry_time_interval.to_le_bytes())?;
+        w.write_all(&maximum_packet_count.to_le_bytes())?;
+        w.write_all(&to_field::<u32, _>(self.index_entries.len())?.to_le_bytes())?;
+        write_all_to(w, &self.index_entries)
+    }
+}
+
+impl<'a> WriteObject for CodecEntry<'a> {
+    fn encoded_size(&self) -> u64 {
+        8 + self.codec_name.len() as u64
+            + self.codec_description.len() as u64
+            + self.codec_information.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.codec_type.to_le_bytes())?;
+        // 名前と説明の長さは UTF-16 の文字数
+        w.write_all(&to_field::<u16, _>(self.codec_name.len() / 2)?.to_le_bytes())?;
+        w.write_all(&self.codec_name)?;
+        w.write_all(&to_field::<u16, _>(self.codec_description.len() / 2)?.to_le_bytes())?;
+        w.write_all(&self.codec_description)?;
+        w.write_all(&to_field::<u16, _>(self.codec_information.len())?.to_le_bytes())?;
+        w.write_all(&self.codec_information)
+    }
+}
+
+impl<'a> WriteObject for CodecListObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 20 + encoded_size_of(&self.codec_entries)
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.reserved)?;
+        w.write_all(&to_field::<u32, _>(self.codec_entries.len())?.to_le_bytes())?;
+        write_all_to(w, &self.codec_entries)
+    }
+}
+
+impl<'a> WriteObject for MarkerEntry<'a> {
+    fn encoded_size(&self) -> u64 {
+        30 + self.marker_description.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.offset.to_le_bytes())?;
+        w.write_all(&self.presentation_time.to_le_bytes())?;
+        w.write_all(&to_field::<u16, _>(12 + self.marker_description.len())?.to_le_bytes())?;
+        w.write_all(&self.send_time.to_le_bytes())?;
+        w.write_all(&self.flags.to_le_bytes())?;
+        w.write_all(&to_field::<u32, _>(self.marker_description.len() / 2)?.to_le_bytes())?;
+        w.write_all(&self.marker_description)
+    }
+}
+
+impl<'a> WriteObject for MarkerObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 24 + self.name.len() as u64 + encoded_size_of(&self.markers)
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.reserved)?;
+        w.write_all(&to_field::<u32, _>(self.markers.len())?.to_le_bytes())?;
+        w.write_all(&self.reserved_2.to_le_bytes())?;
+        w.write_all(&to_field::<u16, _>(self.name.len())?.to_le_bytes())?;
+        w.write_all(&self.name)?;
+        write_all_to(w, &self.markers)
+    }
+}
+
+impl<'a> WriteObject for CommandType<'a> {
+    fn encoded_size(&self) -> u64 {
+        2 + self.command_type_name.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&to_field::<u16, _>(self.command_type_name.len() / 2)?.to_le_bytes())?;
+        w.write_all(&self.command_type_name)
+    }
+}
+
+impl<'a> WriteObject for ScriptCommand<'a> {
+    fn encoded_size(&self) -> u64 {
+        8 + self.command_name.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.presentation_time.to_le_bytes())?;
+        w.write_all(&self.type_index.to_le_bytes())?;
+        w.write_all(&to_field::<u16, _>(self.command_name.len() / 2)?.to_le_bytes())?;
+        w.write_all(&self.command_name)
+    }
+}
+
+impl<'a> WriteObject for ScriptCommandObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE
+            + 20
+            + encoded_size_of(&self.command_types)
+            + encoded_size_of(&self.commands)
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.reserved)?;
+        w.write_all(&to_field::<u16, _>(self.commands.len())?.to_le_bytes())?;
+        w.write_all(&to_field::<u16, _>(self.command_types.len())?.to_le_bytes())?;
+        write_all_to(w, &self.command_types)?;
+        write_all_to(w, &self.commands)
+    }
+}
+
+impl<'a> WriteObject for ContentEncryptionObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE
+            + 16
+            + self.secret_data.len() as u64
+            + self.protection_type.len() as u64
+            + self.key_id.len() as u64
+            + self.license_url.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        for field in &[
+            &self.secret_data,
+            &self.protection_type,
+            &self.key_id,
+            &self.license_url,
+        ] {
+            w.write_all(&to_field::<u32, _>(field.len())?.to_le_bytes())?;
+            w.write_all(field)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteObject for ExtendedContentEncryptionObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 4 + self.data.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&to_field::<u32, _>(self.data.len())?.to_le_bytes())?;
+        w.write_all(&self.data)
+    }
+}
+
+impl<'a> WriteObject for DigitalSignatureObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 8 + self.signature_data.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.signature_type.to_le_bytes())?;
+        w.write_all(&to_field::<u32, _>(self.signature_data.len())?.to_le_bytes())?;
+        w.write_all(&self.signature_data)
+    }
+}
+
+impl<'a> WriteObject for ContentBrandingObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE
+            + 16
+            + self.banner_image_data.len() as u64
+            + self.banner_image_url.len() as u64
+            + self.copyright_url.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.banner_image_type.to_le_bytes())?;
+        for field in &[
+            &self.banner_image_data,
+            &self.banner_image_url,
+            &self.copyright_url,
+        ] {
+            w.write_all(&to_field::<u32, _>(field.len())?.to_le_bytes())?;
+            w.write_all(field)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteObject for MutualExclusionObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 18 + 2 * self.stream_numbers.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.exclusion_type)?;
+        w.write_all(&to_field::<u16, _>(self.stream_numbers.len())?.to_le_bytes())?;
+        for stream_number in &self.stream_numbers {
+            w.write_all(&stream_number.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteObject for GroupMutualExclusionRecord {
+    fn encoded_size(&self) -> u64 {
+        2 + 2 * self.stream_numbers.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&to_field::<u16, _>(self.stream_numbers.len())?.to_le_bytes())?;
+        for stream_number in &self.stream_numbers {
+            w.write_all(&stream_number.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteObject for GroupMutualExclusionObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 18 + encoded_size_of(&self.records)
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.exclusion_type)?;
+        w.write_all(&to_field::<u16, _>(self.records.len())?.to_le_bytes())?;
+        write_all_to(w, &self.records)
+    }
+}
+
+impl<'a> WriteObject for StreamPrioritizationObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 2 + 4 * self.priority_records.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&to_field::<u16, _>(self.priority_records.len())?.to_le_bytes())?;
+        for priority_record in &self.priority_records {
+            w.write_all(&priority_record.stream_number.to_le_bytes())?;
+            w.write_all(&priority_record.priority_flags.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteObject for BandwidthSharingObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 26 + 2 * self.stream_numbers.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.sharing_type)?;
+        w.write_all(&self.data_bitrate.to_le_bytes())?;
+        w.write_all(&self.buffer_size.to_le_bytes())?;
+        w.write_all(&to_field::<u16, _>(self.stream_numbers.len())?.to_le_bytes())?;
+        for stream_number in &self.stream_numbers {
+            w.write_all(&stream_number.to_le_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteObject for LanguageIdRecord<'a> {
+    fn encoded_size(&self) -> u64 {
+        1 + self.language_id.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&[to_field::<u8, _>(self.language_id.len())?])?;
+        w.write_all(&self.language_id)
+    }
+}
+
+impl<'a> WriteObject for LanguageListObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 2 + encoded_size_of(&self.language_id_records)
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&to_field::<u16, _>(self.language_id_records.len())?.to_le_bytes())?;
+        write_all_to(w, &self.language_id_records)
+    }
+}
+
+impl<'a> WriteObject for StreamName<'a> {
+    fn encoded_size(&self) -> u64 {
+        4 + self.stream_name.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.language_id_index.to_le_bytes())?;
+        w.write_all(&to_field::<u16, _>(self.stream_name.len())?.to_le_bytes())?;
+        w.write_all(&self.stream_name)
+    }
+}
+
+impl<'a> WriteObject for PayloadExtensionSystem<'a> {
+    fn encoded_size(&self) -> u64 {
+        22 + self.extension_system_info.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.extension_system_id)?;
+        w.write_all(&self.extension_data_size.to_le_bytes())?;
+        w.write_all(&to_field::<u32, _>(self.extension_system_info.len())?.to_le_bytes())?;
+        w.write_all(&self.extension_system_info)
+    }
+}
+
+impl<'a> WriteObject for ExtendedStreamPropertiesObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE
+            + 64
+            + encoded_size_of(&self.stream_names)
+            + encoded_size_of(&self.payload_extension_systems)
+            + self
+                .stream_props_object
+                .as_ref()
+                .map_or(0, |v| v.encoded_size())
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.start_time.to_le_bytes())?;
+        w.write_all(&self.end_time.to_le_bytes())?;
+        w.write_all(&self.data_bitrate.to_le_bytes())?;
+        w.write_all(&self.buffer_size.to_le_bytes())?;
+        w.write_all(&self.initial_buffer_fullness.to_le_bytes())?;
+        w.write_all(&self.alternate_data_bitrate.to_le_bytes())?;
+        w.write_all(&self.alternate_buffer_size.to_le_bytes())?;
+        w.write_all(&self.alternate_initial_buffer_fullness.to_le_bytes())?;
+        w.write_all(&self.maximum_object_size.to_le_bytes())?;
+        w.write_all(&self.flags.to_le_bytes())?;
+        w.write_all(&self.stream_number.to_le_bytes())?;
+        w.write_all(&self.stream_language_id_index.to_le_bytes())?;
+        w.write_all(&self.average_time_per_frame.to_le_bytes())?;
+        w.write_all(&to_field::<u16, _>(self.stream_names.len())?.to_le_bytes())?;
+        w.write_all(&to_field::<u16, _>(self.payload_extension_systems.len())?.to_le_bytes())?;
+        write_all_to(w, &self.stream_names)?;
+        write_all_to(w, &self.payload_extension_systems)?;
+        if let Some(ref stream_props_object) = self.stream_props_object {
+            stream_props_object.write_to(w)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteObject for MetadataLibraryRecord<'a> {
+    fn encoded_size(&self) -> u64 {
+        12 + self.name.len() as u64 + self.data.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.language_list_index.to_le_bytes())?;
+        w.write_all(&self.stream_number.to_le_bytes())?;
+        w.write_all(&to_field::<u16, _>(self.name.len())?.to_le_bytes())?;
+        w.write_all(&self.data_type.to_le_bytes())?;
+        w.write_all(&to_field::<u32, _>(self.data.len())?.to_le_bytes())?;
+        w.write_all(&self.name)?;
+        w.write_all(&self.data)
+    }
+}
+
+impl<'a> WriteObject for MetadataLibraryObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 2 + encoded_size_of(&self.description_records)
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&to_field::<u16, _>(self.description_records.len())?.to_le_bytes())?;
+        write_all_to(w, &self.description_records)
+    }
+}
+
+impl<'a> WriteObject for CompatibilityObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 2
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&[self.profile, self.mode])
+    }
+}
+
+impl<'a> WriteObject for ErrorCorrectionObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        OBJECT_HEADER_SIZE + 20 + self.error_correction_data.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_object_header(w, &self.object_id, self.encoded_size())?;
+        w.write_all(&self.error_correction_type)?;
+        w.write_all(&to_field::<u32, _>(self.error_correction_data.len())?.to_le_bytes())?;
+        w.write_all(&self.error_correction_data)
+    }
+}
+
+// 解析しなかったオブジェクトは元のバイト列をそのまま書き込む
+impl<'a> WriteObject for RawObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        self.bytes.len() as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.bytes)
+    }
+}
+
+// Length Type に対応する値のバイト数
+fn var_len_size(length_type: u8) -> u32 {
+    match length_type & 0x03 {
+        0 => 0,
+        1 => 1,
+        2 => 2,
+        _ => 4,
+    }
+}
+
+// Length Type に従って 0, 1, 2, 4 bytes の値を書き込む
+fn write_var_len<W: Write>(w: &mut W, length_type: u8, value: u32) -> io::Result<()> {
+    match length_type & 0x03 {
+        0 => Ok(()),
+        1 => w.write_all(&[to_field::<u8, _>(value)?]),
+        2 => w.write_all(&to_field::<u16, _>(value)?.to_le_bytes()),
+        _ => w.write_all(&value.to_le_bytes()),
+    }
+}
+
+impl<'a> DataPacket<'a> {
+    fn is_multiple_payloads(&self) -> bool {
+        self.length_type_flags & 0x01 != 0
+    }
+
+    fn payload_size(&self, payload: &Payload) -> u32 {
+        let mut size = 1
+            + var_len_size(self.property_flags >> 4)
+            + var_len_size(self.property_flags >> 2)
+            + var_len_size(self.property_flags)
+            + payload.replicated_data.len() as u32
+            + payload.payload_data.len() as u32;
+        if self.is_multiple_payloads() {
+            size += var_len_size(self.payload_flags.unwrap_or(0) >> 6);
+        }
+        size
+    }
+
+    fn header_size(&self, length_type_flags: u8) -> u32 {
+        let mut size = 2
+            + var_len_size(length_type_flags >> 5)
+            + var_len_size(length_type_flags >> 1)
+            + var_len_size(length_type_flags >> 3)
+            + 6;
+        if self.error_correction_flags.is_some() {
+            size += 1 + self.error_correction_data.len() as u32;
+        }
+        if self.is_multiple_payloads() {
+            size += 1;
+        }
+        size
+    }
+
+    // packet_size の固定長パケットとして書き込む. Padding Length, Number of Payloads などは内容から計算し、
+    // Padding Length Type で表せない場合は Padding Length Type を広げる
+    pub fn write_to<W: Write>(&self, w: &mut W, packet_size: u32) -> io::Result<()> {
+        let payloads_size: u32 = self.payloads.iter().map(|v| self.payload_size(v)).sum();
+        let mut length_type_flags = self.length_type_flags;
+        let mut padding = i64::from(packet_size)
+            - i64::from(self.header_size(length_type_flags))
+            - i64::from(payloads_size);
+        let max_padding = match (length_type_flags >> 3) & 0x03 {
+            0 => 0,
+            1 => 0xFF,
+            2 => 0xFFFF,
+            _ => i64::from(u32::MAX),
+        };
+        if padding > max_padding {
+            let padding_length_type = if packet_size > 0xFFFF { 0x18 } else { 0x10 };
+            length_type_flags = (length_type_flags & !0x18) | padding_length_type;
+            padding = i64::from(packet_size)
+                - i64::from(self.header_size(length_type_flags))
+                - i64::from(payloads_size);
+        }
+        if padding < 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "payloads exceed the packet size",
+            ));
+        }
+
+        // Error Correction Data Length は4bits, Number of Payloads は6bits
+        if self.error_correction_flags.is_some() && self.error_correction_data.len() > 0x0F {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "error correction data is too long",
+            ));
+        }
+        if self.is_multiple_payloads() && self.payloads.len() > 0x3F {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "too many payloads in a packet",
+            ));
+        }
+
+        if let Some(flags) = self.error_correction_flags {
+            w.write_all(&[(flags & 0xF0) | self.error_correction_data.len() as u8])?;
+            w.write_all(&self.error_correction_data)?;
+        }
+        w.write_all(&[length_type_flags, self.property_flags])?;
+        // 固定長パケットなので Packet Length はパケットのサイズになる
+        write_var_len(w, length_type_flags >> 5, packet_size)?;
+        write_var_len(w, length_type_flags >> 1, self.sequence)?;
+        write_var_len(w, length_type_flags >> 3, padding as u32)?;
+        w.write_all(&self.send_time.to_le_bytes())?;
+        w.write_all(&self.duration.to_le_bytes())?;
+        let payload_length_type = self.payload_flags.unwrap_or(0) >> 6;
+        if self.is_multiple_payloads() {
+            w.write_all(&[payload_length_type << 6 | self.payloads.len() as u8])?;
+        }
+        for payload in &self.payloads {
+            let stream_number = if payload.key_frame {
+                payload.stream_number | 0x80
+            } else {
+                payload.stream_number
+            };
+            w.write_all(&[stream_number])?;
+            write_var_len(w, self.property_flags >> 4, payload.media_object_number)?;
+            write_var_len(
+                w,
+                self.property_flags >> 2,
+                payload.offset_into_media_object,
+            )?;
+            write_var_len(
+                w,
+                self.property_flags,
+                to_field(payload.replicated_data.len())?,
+            )?;
+            w.write_all(&payload.replicated_data)?;
+            if self.is_multiple_payloads() {
+                write_var_len(
+                    w,
+                    payload_length_type,
+                    to_field(payload.payload_data.len())?,
+                )?;
+            }
+            w.write_all(&payload.payload_data)?;
+        }
+        w.write_all(&vec![0; padding as usize])
+    }
+
+    pub fn to_bytes(&self, packet_size: u32) -> io::Result<Vec<u8>> {
+        let mut bytes = Vec::with_capacity(packet_size as usize);
+        self.write_to(&mut bytes, packet_size)?;
+        Ok(bytes)
+    }
+}
+
+impl<'a> HeaderExtensionObject<'a> {
+    // 子オブジェクトを object_order の順に並べたバイト列
+    fn header_extension_data(&self) -> Vec<Vec<u8>> {
+        let mut objects: Vec<(&[u8], Vec<u8>)> = Vec::new();
+        for v in &self.extended_stream_props_objects {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.language_list_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.metadata_library_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        for v in &self.advanced_mutual_exclusion_objects {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        for v in &self.group_mutual_exclusion_objects {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.stream_prioritization_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        for v in &self.bandwidth_sharing_objects {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.compatibility_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        for v in &self.unknown_objects {
+            objects.push((&v.guid, v.to_bytes()));
+        }
+        arrange_objects(&self.object_order, objects)
+    }
+}
+
+impl<'a> WriteObject for HeaderExtensionObject<'a> {
+    fn encoded_size(&self) -> u64 {
+        let header_extension_data_size: usize =
+            self.header_extension_data().iter().map(Vec::len).sum();
+        OBJECT_HEADER_SIZE + 22 + header_extension_data_size as u64
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let header_extension_data = self.header_extension_data();
+        let header_extension_data_size: usize = header_extension_data.iter().map(Vec::len).sum();
+        write_object_header(
+            w,
+            &self.object_id,
+            OBJECT_HEADER_SIZE + 22 + header_extension_data_size as u64,
+        )?;
+        w.write_all(&self.reserved_field_1)?;
+        w.write_all(&self.reserved_field_2.to_le_bytes())?;
+        w.write_all(&to_field::<u32, _>(header_extension_data_size)?.to_le_bytes())?;
+        for object in &header_extension_data {
+            w.write_all(object)?;
+        }
+        Ok(())
+    }
+}
+
+// Header Object に続くオブジェクト
+enum TopLevelObject<'b, 'a> {
+    Data(&'b DataObject<'a>),
+    SimpleIndex(&'b SimpleIndexObject<'a>),
+    Raw(&'b RawObject<'a>),
+}
+
+impl<'b, 'a> TopLevelObject<'b, 'a> {
+    fn encoded_size(&self) -> u64 {
+        match *self {
+            TopLevelObject::Data(v) => v.encoded_size(),
+            TopLevelObject::SimpleIndex(v) => v.encoded_size(),
+            TopLevelObject::Raw(v) => v.encoded_size(),
+        }
+    }
+
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match *self {
+            TopLevelObject::Data(v) => v.write_to(w),
+            TopLevelObject::SimpleIndex(v) => v.write_to(w),
+            TopLevelObject::Raw(v) => v.write_to(w),
+        }
+    }
+}
+
+impl<'a> ASF<'a> {
+    // Header Object の子オブジェクトを header_object_order の順に並べたバイト列.
+    // file_props_object は File Size などを更新したものに置き換える
+    fn header_objects(&self, file_props_object: Option<&FilePropertiesObject>) -> Vec<Vec<u8>> {
+        let mut objects: Vec<(&[u8], Vec<u8>)> = Vec::new();
+        if let Some(v) = file_props_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        for v in &self.stream_props_objects {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.header_extension_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.stream_bitrate_props_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.codec_list_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.marker_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.script_command_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.content_encryption_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.extended_content_encryption_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.digital_signature_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.content_branding_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        for v in &self.bitrate_mutual_exclusion_objects {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        if let Some(ref v) = self.error_correction_object {
+            objects.push((&v.object_id, v.to_bytes()));
+        }
+        for v in &self.unknown_header_objects {
+            objects.push((&v.guid, v.to_bytes()));
+        }
+        arrange_objects(&self.header_object_order, objects)
+    }
+
+    // Header Object に続くオブジェクトを object_order の順に並べる
+    fn top_level_objects<'b>(
+        &'b self,
+        data_object: Option<&'b DataObject<'b>>,
+    ) -> Vec<TopLevelObject<'b, 'b>> {
+        let mut objects = Vec::new();
+        if let Some(v) = data_object {
+            objects.push((&v.object_id[..], TopLevelObject::Data(v)));
+        }
+        for v in &self.simple_index_objects {
+            objects.push((&v.object_id[..], TopLevelObject::SimpleIndex(v)));
+        }
+        for v in &self.unknown_objects {
+            objects.push((&v.guid[..], TopLevelObject::Raw(v)));
+        }
+        arrange_objects(&self.object_order, objects)
+    }
+
+    // ファイル全体を書き込む. Header Object のサイズと子オブジェクトの総数は内容から計算し、
+    // ブロードキャストでない場合は File Properties Object の File Size, Data Packets Count と
+    // Data Object の Total Data Packets も更新する
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        // ブロードキャストでない場合、Total Data Packets はデータパケットのサイズから計算する
+        let data_object = self.data_object.as_ref().map(|v| {
+            let total_data_packets = match self.file_props_object {
+                Some(ref file_props_object)
+                    if !file_props_object.is_broadcast()
+                        && file_props_object.min_data_packet_size > 0 =>
+                {
+                    v.data_packets.len() as u64 / u64::from(file_props_object.min_data_packet_size)
+                }
+                _ => v.total_data_packets,
+            };
+            DataObject {
+                object_id: Cow::Borrowed(&v.object_id),
+                file_id: Cow::Borrowed(&v.file_id),
+                total_data_packets,
+                data_packets: Cow::Borrowed(&v.data_packets),
+                ..**v
+            }
+        });
+        let top_level_objects = self.top_level_objects(data_object.as_ref());
+        let top_level_size: u64 = top_level_objects
+            .iter()
+            .map(TopLevelObject::encoded_size)
+            .sum();
+
+        // File Properties Object のサイズは固定なので、更新前の値でファイル全体のサイズを計算できる
+        let header_size = self.header_object.encoded_size()
+            + self
+                .header_objects(self.file_props_object.as_deref())
+                .iter()
+                .map(|v| v.len() as u64)
+                .sum::<u64>();
+        let file_props_object = self.file_props_object.as_ref().map(|v| {
+            let (file_size, data_packets_count) = if v.is_broadcast() {
+                (v.file_size, v.data_packets_count)
+            } else {
+                (
+                    header_size + top_level_size,
+                    data_object
+                        .as_ref()
+                        .map_or(v.data_packets_count, |v| v.total_data_packets),
+                )
+            };
+            FilePropertiesObject {
+                object_id: Cow::Borrowed(&v.object_id),
+                file_id: Cow::Borrowed(&v.file_id),
+                file_size,
+                data_packets_count,
+                ..**v
+            }
+        });
+        let header_objects = self.header_objects(file_props_object.as_ref());
+
+        let header_object = HeaderObject {
+            object_id: Cow::Borrowed(&self.header_object.object_id),
+            object_size: header_size,
+            num_header_objects: to_field(header_objects.len())?,
+            ..self.header_object
+        };
+        header_object.write_to(w)?;
+        for object in &header_objects {
+            w.write_all(object)?;
+        }
+        for object in &top_level_objects {
+            object.write_to(w)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes)
+            .expect("writing to Vec<u8> never fails");
+        bytes
+    }
+}
+
+#[test]
+fn write_asf_test1() {
+    let input = include_bytes!("../assets/320x180_10fps.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+
+    assert_eq!(asf.to_bytes(), &input[..]);
+}
+
+#[test]
+fn write_asf_test2() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+
+    assert_eq!(asf.to_bytes(), &input[..]);
+    assert_eq!(
+        asf.stream_props_objects[0]
+            .audio_format()
+            .unwrap()
+            .to_bytes(),
+        &asf.stream_props_objects[0].type_specific_data[..]
+    );
+    assert_eq!(
+        asf.stream_props_objects[1]
+            .video_format()
+            .unwrap()
+            .to_bytes(),
+        &asf.stream_props_objects[1].type_specific_data[..]
+    );
+    for v in &asf.stream_props_objects {
+        let bytes = v.to_bytes();
+        assert_eq!(bytes.len() as u64, v.object_size);
+        assert_eq!(
+            crate::parse_stream_props_object(&bytes[16..]).unwrap().1,
+            *v
+        );
+    }
+}
+
+#[test]
+fn write_data_packet_test() {
+    for &input in &[
+        &include_bytes!("../assets/320x180_10fps.asf")[..],
+        &include_bytes!("../assets/kte.asf")[..],
+    ] {
+        let (_, asf) = crate::parse_asf(input).unwrap();
+        let packet_size = asf.file_props_object.as_ref().unwrap().min_data_packet_size;
+        let data_packets = &asf.data_object.as_ref().unwrap().data_packets;
+        for (packet, expected) in asf
+            .packets()
+            .unwrap()
+            .zip(data_packets.chunks(packet_size as usize))
+        {
+            assert_eq!(packet.unwrap().to_bytes(packet_size).unwrap(), expected);
+        }
+    }
+
+    // パディングが Padding Length Type で表せない場合は WORD にする
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, asf) = crate::parse_asf(input).unwrap();
+    let mut packet = asf.packets().unwrap().next().unwrap().unwrap();
+    packet.payloads.truncate(1);
+    let bytes = packet.to_bytes(7200).unwrap();
+    let (_, written) = crate::packet::parse_data_packet(&bytes, 7200).unwrap();
+    assert_eq!(written.length_type_flags, 0x11);
+    assert_eq!(written.payloads, packet.payloads);
+    assert!(packet.to_bytes(100).is_err());
+
+    // Number of Payloads の 6bits に収まらない
+    packet.payloads = (0..64)
+        .map(|_| {
+            asf.packets()
+                .unwrap()
+                .next()
+                .unwrap()
+                .unwrap()
+                .payloads
+                .remove(0)
+        })
+        .collect();
+    let e = packet.to_bytes(u32::MAX).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn write_edited_asf_test() {
+    let input = include_bytes!("../assets/kte.asf");
+    let (_, mut asf) = crate::parse_asf(input).unwrap();
+
+    // 既知のオブジェクトを削除、追加してもサイズや総数が正しく書き込まれる
+    asf.codec_list_object
+        .as_mut()
+        .unwrap()
+        .codec_entries
+        .truncate(1);
+    asf.simple_index_objects.clear();
+    let mut input = vec![0; 8];
+    input.extend_from_slice(
+        &crate::STREAM_PROPERTIES_OBJECT_ERROR_CORRECTION_TYPE_NO_ERROR_CORRECTION_GUID,
+    );
+    input.extend_from_slice(&[0, 0, 0, 0]);
+    let (_, error_correction_object) =
+        crate::correction::parse_error_correction_object(&input).unwrap();
+    asf.error_correction_object = Some(Box::new(error_correction_object));
+    let bytes = asf.to_bytes();
+
+    let (remain, written) = crate::parse_asf(&bytes).unwrap();
+    assert!(remain.is_empty());
+    assert_eq!(written.header_object.num_header_objects, 8);
+    assert_eq!(
+        written.header_object.object_size,
+        asf.header_object.object_size - 58 + 44
+    );
+    assert_eq!(written.codec_list_object.unwrap().codec_entries_count, 1);
+    assert!(written.error_correction_object.unwrap().is_uncorrected());
+    assert!(written.simple_index_objects.is_empty());
+    let file_props_object = written.file_props_object.unwrap();
+    assert_eq!(file_props_object.file_size, bytes.len() as u64);
+    assert_eq!(file_props_object.data_packets_count, 153);
+
+    // データパケットを削除すると総数も更新される
+    let packet_size = asf.file_props_object.as_ref().unwrap().min_data_packet_size as usize;
+    if let Some(ref mut data_object) = asf.data_object {
+        let len = data_object.data_packets.len() - packet_size;
+        data_object.data_packets = Cow::Owned(data_object.data_packets[..len].to_vec());
+    }
+    let bytes = asf.to_bytes();
+    let (remain, written) = crate::parse_asf(&bytes).unwrap();
+    assert!(remain.is_empty());
+    assert_eq!(written.data_object.unwrap().total_data_packets, 152);
+    assert_eq!(written.file_props_object.unwrap().data_packets_count, 152);
+
+    // フィールドに収まらない長さは切り詰めずにエラーにする
+    let language_id_record = LanguageIdRecord {
+        language_id_length: 0,
+        language_id: Cow::Owned(vec![0; 256]),
+    };
+    let mut bytes = Vec::new();
+    let e = language_id_record.write_to(&mut bytes).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::InvalidInput);
+}